@@ -0,0 +1,561 @@
+// the config file, holding named profiles of options like
+//
+//     [profile.release-notes]
+//     validate = "twitter"
+//     numbering = "slash"
+//     hashtags = ["rust", "cli"]
+//
+// selected with `ts --profile release-notes`. a profile can start from another one
+// with `inherits = "base"`, overriding some of its options.
+// it is read as a small subset of TOML:
+// tables, and keys set to strings, integers, booleans, or arrays of them
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "{}", string),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Array(values) => {
+                let values = values.iter().map(Value::to_string).collect::<Vec<_>>();
+                write!(f, "{}", values.join(","))
+            }
+        }
+    }
+}
+
+impl Value {
+    /// The value as it is written in the config file.
+    pub fn toml(&self) -> String {
+        match self {
+            Value::String(string) => format!(
+                "\"{}\"",
+                string
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+                    .replace('\t', "\\t")
+            ),
+            Value::Array(values) => {
+                let values = values.iter().map(Value::toml).collect::<Vec<_>>();
+                format!("[{}]", values.join(", "))
+            }
+            value => value.to_string(),
+        }
+    }
+}
+
+/// Options set by a profile, named like the long flags they stand for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub entries: Vec<(String, Value)>,
+}
+
+impl Profile {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value)
+    }
+
+    /// The profile as the table called `name` in a config file.
+    pub fn toml(&self, name: &str) -> String {
+        let mut toml = format!("[profile.{}]\n", name);
+
+        for (key, value) in &self.entries {
+            toml.push_str(&format!("{} = {}\n", key, value.toml()));
+        }
+
+        toml
+    }
+
+    /// The profile as command line flags, leaving out the ones `is_set` on the command line
+    /// so those win. Values are joined to their flags with `=`, so that none is taken
+    /// for a flag or for more values of the one before it.
+    pub fn args(&self, is_set: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut args = vec![];
+
+        for (key, value) in &self.entries {
+            if is_set(key) {
+                continue;
+            }
+
+            match value {
+                Value::Boolean(true) => args.push(format!("--{}", key)),
+                Value::Boolean(false) => (),
+                Value::Array(values) => {
+                    for value in values {
+                        args.push(format!("--{}={}", key, value));
+                    }
+                }
+                value => args.push(format!("--{}={}", key, value)),
+            }
+        }
+
+        args
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    pub path: PathBuf,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// `path`, or the default config file:
+    /// `$TS_CONFIG`, or `ts/config.toml` in `$XDG_CONFIG_HOME` or `~/.config`.
+    pub fn path(path: Option<&Path>) -> Result<PathBuf, String> {
+        match path {
+            Some(path) => Ok(path.to_path_buf()),
+            None => default_path()
+                .ok_or_else(|| "there is no config file, set one with --config".to_string()),
+        }
+    }
+
+    /// The config at `path`, or the default one.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = Config::path(path)?;
+        let source = fs::read_to_string(&path)
+            .map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+
+        let mut config =
+            Config::parse(&source).map_err(|error| format!("{}: {}", path.display(), error))?;
+        config.path = path;
+
+        Ok(config)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut profile = None;
+
+        for (line_number, line) in source.lines().enumerate() {
+            let error = |message: &str| format!("line {}: {}", line_number + 1, message);
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[') {
+                let table = table
+                    .strip_suffix(']')
+                    .ok_or_else(|| error("expected `]` after the table name"))?
+                    .trim();
+                let name = table
+                    .strip_prefix("profile.")
+                    .filter(|name| is_bare_key(name))
+                    .ok_or_else(|| error("expected a table like `[profile.name]`"))?;
+
+                config.profiles.entry(name.to_string()).or_default();
+                profile = Some(name.to_string());
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`"))?;
+            let key = key.trim();
+            if !is_bare_key(key) {
+                return Err(error(&format!("`{}` is not a key", key)));
+            }
+            let value = parse_value(value.trim()).map_err(|message| error(&message))?;
+
+            let profile = profile
+                .as_ref()
+                .ok_or_else(|| error("options go in a `[profile.name]` table"))?;
+            let entries = &mut config.profiles.get_mut(profile).unwrap().entries;
+            if entries.iter().any(|(existing, _)| existing == key) {
+                return Err(error(&format!("`{}` is set twice", key)));
+            }
+            entries.push((key.to_string(), value));
+        }
+
+        Ok(config)
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile, String> {
+        self.profiles.get(name).ok_or_else(|| {
+            let names = self.profiles.keys().cloned().collect::<Vec<String>>();
+            format!(
+                "{} has no profile `{}`, only: {}",
+                self.path.display(),
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// The profile called `name` with what it inherits: the options of the profile
+    /// named by its `inherits`, resolved the same way, overridden by its own.
+    pub fn resolve(&self, name: &str) -> Result<Profile, String> {
+        let mut chain = vec![];
+        let mut next = Some(name);
+
+        while let Some(name) = next {
+            if chain.contains(&name) {
+                chain.push(name);
+                return Err(format!(
+                    "profiles inherit in a cycle: {}",
+                    chain.join(" -> ")
+                ));
+            }
+            chain.push(name);
+
+            next = match self.profile(name)?.get("inherits") {
+                Some(Value::String(base)) => Some(base),
+                Some(_) => {
+                    return Err(format!(
+                        "`inherits` in profile `{}` must be the name of a profile",
+                        name
+                    ))
+                }
+                None => None,
+            };
+        }
+
+        let mut resolved = Profile::default();
+        for name in chain.iter().rev() {
+            for (key, value) in &self.profiles[*name].entries {
+                if key == "inherits" {
+                    continue;
+                }
+
+                match resolved
+                    .entries
+                    .iter_mut()
+                    .find(|(existing, _)| existing == key)
+                {
+                    Some(entry) => entry.1 = value.clone(),
+                    None => resolved.entries.push((key.clone(), value.clone())),
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+/// `source` with `key` of `profile` set to `value`, replacing the line that sets it,
+/// or adding one at the end of the table, or a new table at the end.
+/// Everything else, comments included, is kept as it was.
+pub fn set(source: &str, profile: &str, key: &str, value: &Value) -> String {
+    let line = format!("{} = {}", key, value.toml());
+    let mut lines = source.lines().map(str::to_string).collect::<Vec<String>>();
+
+    let mut in_table = false;
+    // the line setting `key`, or the last line with something on it in the table
+    let (mut found, mut table_end) = (None, None);
+    for (number, text) in lines.iter().enumerate() {
+        let text = strip_comment(text).trim();
+        if let Some(table) = text.strip_prefix('[') {
+            in_table = table.trim_end_matches(']').trim() == format!("profile.{}", profile);
+            if in_table {
+                table_end = Some(number);
+            }
+        } else if in_table && !text.is_empty() {
+            table_end = Some(number);
+            if text.split('=').next().map(str::trim) == Some(key) {
+                found = Some(number);
+            }
+        }
+    }
+
+    match (found, table_end) {
+        (Some(number), _) => lines[number] = line,
+        (None, Some(number)) => lines.insert(number + 1, line),
+        (None, None) => {
+            if lines.last().is_some_and(|last| !last.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[profile.{}]", profile));
+            lines.push(line);
+        }
+    }
+
+    let mut source = lines.join("\n");
+    source.push('\n');
+    source
+}
+
+fn default_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("TS_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|directory| directory.join("ts").join("config.toml"))
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// `line` up to a `#` that isn't in a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..offset],
+            _ => (),
+        }
+    }
+
+    line
+}
+
+pub fn parse_value(value: &str) -> Result<Value, String> {
+    let (value, rest) = parse_prefix(value)?;
+
+    if rest.trim().is_empty() {
+        Ok(value)
+    } else {
+        Err(format!("unexpected `{}` after the value", rest.trim()))
+    }
+}
+
+// the value at the start of `source`, and what follows it
+fn parse_prefix(source: &str) -> Result<(Value, &str), String> {
+    let source = source.trim_start();
+
+    if let Some(string) = source.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = string.char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &string[offset + 1..])),
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, '"')) => value.push('"'),
+                    Some((_, '\\')) => value.push('\\'),
+                    _ => return Err("unknown escape in string".to_string()),
+                },
+                c => value.push(c),
+            }
+        }
+
+        Err("unterminated string".to_string())
+    } else if let Some(mut rest) = source.strip_prefix('[') {
+        let mut values = vec![];
+
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), after));
+            }
+
+            let (value, after) = parse_prefix(rest)?;
+            values.push(value);
+
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("expected `,` or `]` in array".to_string());
+            }
+        }
+    } else {
+        let end = source
+            .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+            .unwrap_or(source.len());
+        let (word, rest) = source.split_at(end);
+
+        let value = match word {
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            word => Value::Integer(
+                word.replace('_', "")
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a string, integer, or boolean", word))?,
+            ),
+        };
+
+        Ok((value, rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        # posting styles
+        [profile.release-notes]
+        validate = "twitter"
+        max-tweet-length = 280
+        hashtags = ["rust", "cli"] # trailing comment
+        ellipsis = true
+        escape-mentions = false
+
+        [profile.rant]
+        prefix = "🧵 #{{index}} "
+    "#;
+
+    #[test]
+    fn it_parses_profiles() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        assert_eq!(
+            config.profile("release-notes").unwrap().entries,
+            vec![
+                ("validate".to_string(), Value::String("twitter".to_string())),
+                ("max-tweet-length".to_string(), Value::Integer(280)),
+                (
+                    "hashtags".to_string(),
+                    Value::Array(vec![
+                        Value::String("rust".to_string()),
+                        Value::String("cli".to_string())
+                    ])
+                ),
+                ("ellipsis".to_string(), Value::Boolean(true)),
+                ("escape-mentions".to_string(), Value::Boolean(false)),
+            ]
+        );
+        assert_eq!(
+            config.profile("rant").unwrap().args(|_| false),
+            vec!["--prefix=🧵 #{{index}} "]
+        );
+        assert!(config.profile("missing").is_err());
+    }
+
+    #[test]
+    fn it_leaves_out_flags_set_on_the_command_line() {
+        let config = Config::parse(CONFIG).unwrap();
+        let args = config
+            .profile("release-notes")
+            .unwrap()
+            .args(|flag| flag == "max-tweet-length");
+
+        assert_eq!(
+            args,
+            vec![
+                "--validate=twitter",
+                "--hashtags=rust",
+                "--hashtags=cli",
+                "--ellipsis"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_resolves_inherited_options() {
+        let config = Config::parse(
+            r#"
+            [profile.base]
+            max-tweet-length = 280
+            hashtags = ["rust"]
+
+            [profile.short]
+            inherits = "base"
+            max-tweet-length = 140
+            ellipsis = true
+
+            [profile.shorter]
+            inherits = "short"
+            prefix = "\"quoted\" "
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.resolve("shorter").unwrap().toml("shorter"),
+            "[profile.shorter]\n\
+             max-tweet-length = 140\n\
+             hashtags = [\"rust\"]\n\
+             ellipsis = true\n\
+             prefix = \"\\\"quoted\\\" \"\n"
+        );
+        assert_eq!(
+            config.resolve("base").unwrap(),
+            config.profiles["base"].clone()
+        );
+    }
+
+    #[test]
+    fn it_refuses_inheritance_cycles() {
+        let config =
+            Config::parse("[profile.a]\ninherits = \"b\"\n[profile.b]\ninherits = \"a\"\n")
+                .unwrap();
+
+        assert_eq!(
+            config.resolve("a").unwrap_err(),
+            "profiles inherit in a cycle: a -> b -> a"
+        );
+        assert!(Config::parse("[profile.a]\ninherits = \"c\"\n")
+            .unwrap()
+            .resolve("a")
+            .is_err());
+    }
+
+    #[test]
+    fn it_sets_options_in_place() {
+        let source = "# mine\n[profile.a]\nlength = 1 # short\n\n[profile.b]\nformat = \"plain\"\n";
+        let set = |profile, key, value| set(source, profile, key, &value);
+
+        assert_eq!(
+            set("a", "length", Value::Integer(2)),
+            "# mine\n[profile.a]\nlength = 2\n\n[profile.b]\nformat = \"plain\"\n"
+        );
+        assert_eq!(
+            set("a", "ellipsis", Value::Boolean(true)),
+            "# mine\n[profile.a]\nlength = 1 # short\nellipsis = true\n\n[profile.b]\nformat = \"plain\"\n"
+        );
+        assert_eq!(
+            set("c", "validate", Value::String("twitter".to_string())),
+            format!("{}\n[profile.c]\nvalidate = \"twitter\"\n", source)
+        );
+        assert_eq!(
+            super::set("", "c", "ellipsis", &Value::Boolean(true)),
+            "[profile.c]\nellipsis = true\n"
+        );
+    }
+
+    #[test]
+    fn it_points_at_the_line_of_a_mistake() {
+        let error = |source| Config::parse(source).unwrap_err();
+
+        assert_eq!(
+            error("a = 1"),
+            "line 1: options go in a `[profile.name]` table"
+        );
+        assert_eq!(
+            error("[profile.x]\nprefix = \"open"),
+            "line 2: unterminated string"
+        );
+        assert_eq!(
+            error("[profile.x]\n\nlength = 28O"),
+            "line 3: `28O` is not a string, integer, or boolean"
+        );
+        assert_eq!(
+            error("[settings]"),
+            "line 1: expected a table like `[profile.name]`"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// `ts doctor`, checking what ts depends on outside itself and saying how to fix it.
+// everything is checked locally, and nothing is sent anywhere
+
+use crate::config::Config;
+use crate::{check_profiles, git, report, terminal};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug)]
+struct Diagnosis {
+    status: Status,
+    subject: &'static str,
+    message: String,
+    help: Option<String>,
+}
+
+impl Diagnosis {
+    fn ok(subject: &'static str, message: String) -> Self {
+        Self {
+            status: Status::Ok,
+            subject,
+            message,
+            help: None,
+        }
+    }
+
+    fn warning(subject: &'static str, message: String, help: String) -> Self {
+        Self {
+            status: Status::Warning,
+            subject,
+            message,
+            help: Some(help),
+        }
+    }
+
+    fn error(subject: &'static str, message: String, help: String) -> Self {
+        Self {
+            status: Status::Error,
+            subject,
+            message,
+            help: Some(help),
+        }
+    }
+}
+
+/// Print a line for each check, failing if any found an error.
+pub fn run(
+    config: Option<&Path>,
+    interactive: bool,
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let diagnoses = [
+        check_config(config),
+        check_locale(),
+        check_terminal(interactive),
+        check_git(),
+        check_crash_report(),
+    ];
+
+    for diagnosis in &diagnoses {
+        let status = match diagnosis.status {
+            Status::Ok => "ok",
+            Status::Warning => "warning",
+            Status::Error => "error",
+        };
+        writeln!(
+            out,
+            "{:<7} {}: {}",
+            status, diagnosis.subject, diagnosis.message
+        )?;
+        if let Some(help) = &diagnosis.help {
+            writeln!(out, "        help: {}", help)?;
+        }
+    }
+
+    let errors = diagnoses
+        .iter()
+        .filter(|diagnosis| diagnosis.status == Status::Error)
+        .count();
+    match errors {
+        0 => Ok(()),
+        1 => Err("`ts doctor` found a problem".into()),
+        _ => Err(format!("`ts doctor` found {} problems", errors).into()),
+    }
+}
+
+fn check_config(path: Option<&Path>) -> Diagnosis {
+    let path = match Config::path(path) {
+        Ok(path) => path,
+        Err(_) => {
+            return Diagnosis::ok(
+                "config",
+                "no config file, since none of $TS_CONFIG, $XDG_CONFIG_HOME, or $HOME is set"
+                    .to_string(),
+            )
+        }
+    };
+    if !path.exists() {
+        return Diagnosis::ok(
+            "config",
+            format!(
+                "no config file at {}, which is fine without profiles",
+                path.display()
+            ),
+        );
+    }
+
+    let checked = Config::load(Some(&path))
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|config| check_profiles(&config).map(|()| config));
+    match checked {
+        Ok(config) => Diagnosis::ok(
+            "config",
+            format!(
+                "{} has {} valid profile(s)",
+                path.display(),
+                config.profiles.len()
+            ),
+        ),
+        Err(error) => Diagnosis::error(
+            "config",
+            error.to_string(),
+            "fix it with `ts config edit`, or set one option with `ts config set`".to_string(),
+        ),
+    }
+}
+
+// emoji and accents print as garbage in a terminal whose locale isn't UTF-8
+fn check_locale() -> Diagnosis {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (name, value))
+    });
+    let help = "set LANG to a UTF-8 locale, like `export LANG=en_US.UTF-8`".to_string();
+
+    match locale {
+        Some((name, value)) => {
+            let lowercase = value.to_lowercase();
+            if lowercase.contains("utf-8") || lowercase.contains("utf8") {
+                Diagnosis::ok("locale", format!("{}={} is UTF-8", name, value))
+            } else {
+                Diagnosis::warning(
+                    "locale",
+                    format!(
+                        "{}={} is not UTF-8, so emoji and accents may print wrong",
+                        name, value
+                    ),
+                    help,
+                )
+            }
+        }
+        None => Diagnosis::warning(
+            "locale",
+            "no locale is set, so emoji and accents may print wrong".to_string(),
+            help,
+        ),
+    }
+}
+
+fn check_terminal(interactive: bool) -> Diagnosis {
+    if !interactive {
+        return Diagnosis::ok(
+            "terminal",
+            "stdout is not a terminal, so `--format auto` prints plain tweets".to_string(),
+        );
+    }
+
+    match terminal::terminal_width() {
+        Some(width) => Diagnosis::ok(
+            "terminal",
+            format!("{} columns wide, which `--wrap` wraps previews to", width),
+        ),
+        None => Diagnosis::warning(
+            "terminal",
+            "the terminal doesn't say how wide it is".to_string(),
+            "wrap previews with `--wrap-width` instead of `--wrap`".to_string(),
+        ),
+    }
+}
+
+fn check_git() -> Diagnosis {
+    match git(&["--version"]) {
+        Ok(version) => Diagnosis::ok("git", format!("{}, for `--from-git`", version)),
+        Err(_) => Diagnosis::warning(
+            "git",
+            "git isn't on the PATH".to_string(),
+            "install git to use `--from-git`, or pass the text another way".to_string(),
+        ),
+    }
+}
+
+fn check_crash_report() -> Diagnosis {
+    let path = report::path();
+
+    if path.exists() {
+        Diagnosis::warning(
+            "crashes",
+            format!(
+                "an earlier run crashed and left a report at {}",
+                path.display()
+            ),
+            "print it with `ts --report` to attach to an issue, then delete it".to_string(),
+        )
+    } else {
+        Diagnosis::ok("crashes", "no crash reports".to_string())
+    }
+}
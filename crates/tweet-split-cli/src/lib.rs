@@ -0,0 +1,2101 @@
+// the whole of `ts`: arguments, reading input, and printing tweets,
+// so that other binaries can embed it and tests can run it in-process
+
+use regex::Regex;
+use std::collections::HashSet;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::SystemTime;
+use structopt::*;
+use tweet_split_core::a11y;
+use tweet_split_core::batch;
+use tweet_split_core::changelog::{self, Bullets};
+use tweet_split_core::confusables;
+use tweet_split_core::count::{CountMode, Counter};
+use tweet_split_core::decorate::{Ellipsis, EscapeMentions, Hashtags, ReplyMentions, WordJoiner};
+use tweet_split_core::entities::{self, EntityKind};
+use tweet_split_core::explain;
+use tweet_split_core::fences;
+use tweet_split_core::json::Json;
+use tweet_split_core::numbering::{
+    Numbering, NumberingPosition, NumberingScope, NumberingStyle, Positions,
+};
+use tweet_split_core::platform::{Platform, Preset};
+use tweet_split_core::preprocess::{self, Pipeline, TextFilter};
+use tweet_split_core::sections::{self, HeadingStyle};
+use tweet_split_core::shorten::Shortener;
+use tweet_split_core::sms;
+use tweet_split_core::template::{Date, Template, TweetTemplate};
+use tweet_split_core::thread::Thread;
+use tweet_split_core::validate::{self, Rules};
+use tweet_split_core::{
+    OverflowPolicy, Paragraphs, SignOff, SplitOptions, SplitStrategy, TweetSplitError,
+};
+
+mod config;
+mod doctor;
+#[cfg(feature = "qr")]
+mod qr;
+mod report;
+mod terminal;
+
+// `ts` with a subcommand, or with only the options of `ts split`
+#[derive(Debug, StructOpt)]
+#[structopt(name = "ts", setting = clap::AppSettings::ArgsNegateSubcommands)]
+struct Ts {
+    #[structopt(flatten)]
+    options: Options,
+
+    #[structopt(subcommand)]
+    subcommand: Option<Subcommand>,
+}
+
+impl Ts {
+    fn into_subcommand(self) -> Subcommand {
+        self.subcommand.unwrap_or(Subcommand::Split(self.options))
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum Subcommand {
+    /// Split the text into tweets, as `ts` does without a subcommand
+    Split(Options),
+    /// Print how many tweets the text takes, or with `--length` its length
+    Count(Options),
+    /// Check that the text fits in one tweet, and `--validate`'s rules
+    Check(Options),
+    /// Split a release's notes, from `--from-git`'s latest tag or a `--changelog` section,
+    /// for announcing it
+    Announce(Options),
+    /// Join tweets printed with `--format plain` back into text
+    Join(Join),
+    /// Post the thread, which ts can't do yet
+    Post,
+    /// Explain an error code like TS006
+    Explain {
+        /// The code, like TS006
+        code: Option<String>,
+    },
+    /// Manage the config file and its profiles
+    Config(ConfigCommand),
+    /// Check the config file and what ts needs from the system
+    Doctor(Doctor),
+}
+
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(name = "ts")]
+struct Options {
+    /// Location of text to tweetify
+    #[structopt(short = "i", long, parse(from_str))]
+    input_path: Option<PathBuf>,
+
+    /// Split every draft in a JSON Lines (or, with a .csv extension, CSV) file as its own thread.
+    /// Drafts need a `text` field and may set name, max_length, prefix, suffix, numbering, and end_marker.
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["input-path", "changelog", "from-git"])]
+    batch: Option<PathBuf>,
+
+    /// Take the positional arguments as files, or globs of files like `notes/*.md`,
+    /// and split each as its own thread under a `==> path <==` header
+    #[structopt(long, conflicts_with_all = &["input-path", "batch", "changelog", "from-git"])]
+    files: bool,
+
+    /// Use the annotated message of the latest git tag as the text to tweetify
+    #[structopt(long, conflicts_with = "input-path")]
+    from_git: bool,
+
+    /// Use a version's section of a keep-a-changelog style CHANGELOG as the text to tweetify
+    #[structopt(long, parse(from_os_str), conflicts_with = "input-path")]
+    changelog: Option<PathBuf>,
+
+    /// The version to take from `--changelog`, the latest git tag with `--from-git`
+    #[structopt(long)]
+    changelog_version: Option<String>,
+
+    /// The heading each version's section starts with in `--changelog`, like `#` for `# 1.4.0`
+    #[structopt(long, default_value = "##", parse(try_from_str = parse_heading))]
+    changelog_heading: usize,
+
+    /// What to do with changelog bullets: sentences (prose) or keep (one bullet per line, kept together)
+    #[structopt(long, default_value = "sentences")]
+    bullets: Bullets,
+
+    /// The maximum length of a tweet, in characters, 280 unless `--platform` sets another
+    #[structopt(short = "l", long)]
+    max_tweet_length: Option<usize>,
+
+    /// What tweet lengths are measured in: bytes (the default), utf16 (code units, as in JavaScript), graphemes (what a reader sees as one
+    /// character, like é or 👩‍👩‍👧‍👦), twitter (Twitter's weighted count, where CJK and emoji
+    /// count 2), mastodon (code points, with links counting 23), or gsm7 (SMS septets)
+    #[structopt(long)]
+    count: Option<CountMode>,
+
+    /// Take the length limit and count from a platform's preset: twitter, mastodon, bluesky,
+    /// threads, discord, or sms. `-l` and `--count` override them. With sms, the
+    /// input's encoding picks the count and whether tweets are single or concatenated segments
+    #[structopt(long)]
+    platform: Option<Preset>,
+
+    /// Remove `<!-- ... -->` comments and lines starting with `//~` before splitting
+    #[structopt(short = "c", long)]
+    strip_comments: bool,
+
+    /// Replace `${VAR}` references with values from `--define` or the environment
+    #[structopt(short = "s", long)]
+    substitute: bool,
+
+    /// Define a substitution variable as `key=value`; implies `--substitute`
+    #[structopt(short = "D", long = "define", parse(try_from_str = parse_definition), number_of_values = 1)]
+    definitions: Vec<(String, String)>,
+
+    /// Clean up the text before splitting, with filters run in the order given:
+    /// trim, newlines, html, comments, substitute, link-last (move the first link, whose
+    /// preview card would open the thread, to the end), dedupe-links (drop repeated links),
+    /// bullets (replace Markdown list bullets with `--list-bullet`), styles (write
+    /// `**bold**` and `*italic*` in styled Unicode letters, which screen readers stumble on),
+    /// or camel-hashtags (write hashtags of several words in CamelCase, which they read better).
+    /// `-c` and `-s` add their filters after these if they are not listed
+    #[structopt(long = "filter", number_of_values = 1, possible_values = &FILTERS)]
+    filters: Vec<String>,
+
+    /// What `--filter bullets` puts in place of `-`, `*`, and `+` list bullets
+    #[structopt(long, default_value = "•")]
+    list_bullet: String,
+
+    /// Make `--filter bullets` number ordered lists with keycap emoji, like 1️⃣ for `1.`
+    #[structopt(long)]
+    list_keycaps: bool,
+
+    /// A hashtag spelled in CamelCase, like `#RustLang`, that `--filter camel-hashtags`
+    /// spells any hashtag the same but for case with. Implies the filter
+    #[structopt(long = "camel-hashtag", number_of_values = 1)]
+    camel_hashtags: Vec<String>,
+
+    /// A file of words, one per line, that `--filter camel-hashtags` breaks lowercase hashtags
+    /// into, like `#rustlang` into `#RustLang` with `rust` and `lang`. Implies the filter
+    #[structopt(long, parse(from_os_str))]
+    hashtag_words: Option<PathBuf>,
+
+    // the words read from `--hashtag-words`
+    #[structopt(skip)]
+    hashtag_word_list: HashSet<String>,
+
+    /// Replace links with the short links mapped to them in this file, one
+    /// `long-link short-link` pair per line, after the other filters.
+    /// JSON output keeps the original of each link as `expanded_url`
+    #[structopt(long, parse(from_os_str))]
+    shorten: Option<PathBuf>,
+
+    // the mapping read from `--shorten`
+    #[structopt(skip)]
+    shortener: Option<Shortener>,
+
+    /// Template rendered before every tweet, e.g. `{{index}}/{{total}} `
+    #[structopt(long, parse(try_from_str = Template::parse))]
+    prefix: Option<Template>,
+
+    /// Template rendered after every tweet, e.g. ` ({{today:%b %e}})`
+    #[structopt(long, parse(try_from_str = Template::parse))]
+    suffix: Option<Template>,
+
+    /// Template for the whole of every tweet, in place of `--prefix` and `--suffix`,
+    /// e.g. `{{index}}/{{total}} {{text}} 🧵`
+    #[structopt(long, parse(try_from_str = TweetTemplate::parse), conflicts_with_all = &["prefix", "suffix"])]
+    template: Option<TweetTemplate>,
+
+    /// Number each tweet: slash (1/5), of (1 of 5), bracketed ([1/5]), thread (🧵1/5), roman, or letters
+    #[structopt(short = "n", long)]
+    numbering: Option<NumberingStyle>,
+
+    /// Where the counter goes: prefix or suffix
+    #[structopt(long, default_value = "suffix")]
+    numbering_position: NumberingPosition,
+
+    /// Which tweets get a counter: a comma-separated list of first, middle, last, or all
+    #[structopt(long, default_value = "all")]
+    number_on: Positions,
+
+    /// What counters count through: thread (every tweet), or section (the tweets from
+    /// each Markdown heading to the next, split on their own and numbered like 2.3/2.5).
+    /// Only the counters restart; the thread still ends once, after the last section
+    #[structopt(long, default_value = "thread")]
+    numbering_scope: NumberingScope,
+
+    /// Put each Markdown heading in a tweet of its own introducing its section, after a
+    /// marker like `--promote-headings ▶`, or in capitals with `--promote-headings uppercase`
+    #[structopt(long, conflicts_with = "code-fences")]
+    promote_headings: Option<HeadingStyle>,
+
+    /// Append a terminator to the final tweet
+    #[structopt(long)]
+    end_marker: bool,
+
+    /// The terminator added by `--end-marker`
+    #[structopt(long, default_value = "/end")]
+    end_marker_text: String,
+
+    /// End every tweet but the last with "…"
+    #[structopt(long)]
+    ellipsis: bool,
+
+    /// Start every tweet but the first with "…"
+    #[structopt(long)]
+    leading_ellipsis: bool,
+
+    /// Hashtags to append, with or without their `#`, like `--hashtags rust,cli`
+    #[structopt(long, use_delimiter = true, number_of_values = 1)]
+    hashtags: Vec<String>,
+
+    /// Which tweets get the hashtags: a comma-separated list of first, middle, last, or all
+    #[structopt(long, default_value = "last")]
+    hashtags_on: Positions,
+
+    /// Leave room in every tweet after the first for the mentions a platform adds
+    /// to replies, like `--reply-mentions ckampfe`
+    #[structopt(long, use_delimiter = true, number_of_values = 1)]
+    reply_mentions: Vec<String>,
+
+    /// Write the `--reply-mentions` into the replies too, instead of only leaving room
+    #[structopt(long)]
+    render_reply_mentions: bool,
+
+    /// Put a `.` before tweets starting with a @mention, so they don't post as replies
+    #[structopt(long)]
+    escape_mentions: bool,
+
+    /// Put an invisible word joiner (U+2060) before a tweet that starts with punctuation
+    /// like "…" or "—", which some clients render oddly at the start of a line
+    #[structopt(long)]
+    word_joiner: bool,
+
+    /// How paragraphs map onto tweets: reflow, keep (one tweet per paragraph), or auto (keep if they all fit)
+    #[structopt(long, default_value = "reflow")]
+    paragraphs: Paragraphs,
+
+    /// Keep the last N lines intact in their own final tweet
+    #[structopt(long, conflicts_with = "sign-off-delimiter")]
+    sign_off_lines: Option<usize>,
+
+    /// Keep everything from the last line matching this delimiter, like `--`, in its own final tweet
+    #[structopt(long)]
+    sign_off_delimiter: Option<String>,
+
+    /// Avoid splitting initials and initialisms like "J. R. R. Tolkien" across tweets
+    #[structopt(long)]
+    keep_initials: bool,
+
+    /// Avoid splitting numbers from their units, like "10 MB" or "3 000 €", across tweets
+    #[structopt(long)]
+    keep_units: bool,
+
+    /// Avoid splitting matches of this regular expression across tweets
+    #[structopt(long, number_of_values = 1)]
+    keep_together: Vec<Regex>,
+
+    /// Where tweets prefer to end: words (after as many as fit), sentences (at the last
+    /// sentence end that fits, if the tweet is at least half full), lines (at the last
+    /// line end that fits), or preformatted (only at line ends, failing on a line longer
+    /// than a tweet, for code)
+    #[structopt(long, default_value = "words")]
+    strategy: SplitStrategy,
+
+    /// Close a fenced code block at the end of a tweet that ends inside it and reopen it,
+    /// language and all, at the start of the next, so each renders as code. On with
+    /// `--platform discord`
+    #[structopt(long)]
+    code_fences: bool,
+
+    /// What to do with a word longer than a tweet: error, split (across tweets, between
+    /// characters), allow (in an overlong tweet of its own), or truncate (dropping what doesn't fit)
+    #[structopt(long, default_value = "error")]
+    overflow: OverflowPolicy,
+
+    /// Print a stable, content-derived id and a tab before each tweet
+    #[structopt(long)]
+    ids: bool,
+
+    /// How to print tweets: human (numbered, with lengths), plain (one tweet per line, or per `--separator`),
+    /// json (an array of an object per tweet, with where its room went), jsonl (those objects
+    /// one per line), csv (index, total, length, and tweet columns, and id with `--ids`),
+    /// yaml (a list of the same), or auto (human in a terminal, plain when piped)
+    #[structopt(long, default_value = "auto")]
+    format: OutputFormat,
+
+    /// How `--format plain` escapes each tweet: none (as it should be posted), shell
+    /// (newlines as `\\n` and quotes behind backslashes), or json (as a JSON string)
+    #[structopt(long, default_value = "none")]
+    escape: Escape,
+
+    /// What `--format plain` prints after each tweet instead of a newline, like `---`
+    /// on a line of its own to tell multi-line tweets apart
+    #[structopt(long)]
+    separator: Option<String>,
+
+    /// Follow each tweet with a NUL byte instead of a newline, for `xargs -0`,
+    /// and print as `--format plain` whatever `--format` says
+    #[structopt(short = "0", long, conflicts_with_all = &["batch", "files", "qr", "separator"])]
+    null: bool,
+
+    /// In human output, re-wrap each tweet to the terminal width for display,
+    /// without changing the tweets themselves
+    #[structopt(long)]
+    wrap: bool,
+
+    /// Re-wrap human output to this many columns instead of the terminal width
+    #[structopt(long)]
+    wrap_width: Option<usize>,
+
+    /// Draw each tweet as a QR code, to scan it onto a phone (needs the `qr` feature)
+    #[structopt(long, conflicts_with = "content-hash")]
+    qr: bool,
+
+    /// Check every tweet against a platform's rules besides length, twitter or bluesky,
+    /// warning about too many mentions, hashtags, or links, forbidden characters, and,
+    /// on twitter, tweets that repeat an earlier one
+    #[structopt(long)]
+    validate: Option<Platform>,
+
+    /// Fail instead of warning when a tweet breaks the rules of `--validate`
+    #[structopt(long, requires = "validate")]
+    strict: bool,
+
+    /// Warn about words with lookalike letters from another script, like a Cyrillic "а"
+    /// in a Latin word, and invisible characters like soft hyphens, often pasted in from PDFs
+    #[structopt(long)]
+    check_confusables: bool,
+
+    /// Check every tweet for what reads badly with a screen reader: letters styled with
+    /// Unicode symbols, long runs of emoji, and hashtags not in CamelCase.
+    /// `warn` warns about them, and `strict` fails the run
+    #[structopt(long)]
+    a11y: Option<A11y>,
+
+    /// Append zero-width spaces to tweets that repeat an earlier one, where they fit,
+    /// so a platform that refuses duplicate posts takes them
+    #[structopt(long)]
+    vary_duplicates: bool,
+
+    /// Refuse any input that would need the network, such as a URL given as a path
+    #[structopt(long)]
+    offline: bool,
+
+    /// Start from the options of this profile in the config file, like `--profile release-notes`
+    /// for a `[profile.release-notes]` table. Flags given on the command line win
+    #[structopt(long)]
+    profile: Option<String>,
+
+    /// The config file with the profiles, instead of `$TS_CONFIG` or `~/.config/ts/config.toml`
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Print only a hash of the thread's text, ignoring decorations, to detect unchanged threads
+    #[structopt(long, conflicts_with = "ids")]
+    content_hash: bool,
+
+    /// Print how full each tweet is instead of the tweets: each one's length and share
+    /// of the limit, the thread's tweets and total length, and the least, mean, and most used
+    #[structopt(long, conflicts_with = "content-hash")]
+    stats: bool,
+
+    /// Write each tweet, exactly as it should be posted, to a file of its own in this
+    /// directory, named for its number like `001.txt`, instead of printing it.
+    /// Numbered files left from an earlier, longer thread, like `004.txt` after a thread of 3,
+    /// are removed; other files are kept
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["batch", "files", "content-hash", "stats"])]
+    out_dir: Option<PathBuf>,
+
+    /// Run ts on a few built-in inputs, check what it prints, and exit,
+    /// failing with a nonzero status if anything is off
+    #[structopt(long)]
+    self_test: bool,
+
+    /// Print the bug report written by the last crash
+    #[structopt(long)]
+    report: bool,
+
+    /// Split a built-in sample with the given options, print `ok`, and exit,
+    /// failing with a nonzero status if the split fails or produces an overlong tweet
+    #[structopt(long)]
+    healthcheck: bool,
+
+    /// Print only how many tweets the text splits into, not the tweets,
+    /// like `ts split --count-tweets` or `ts count`
+    #[structopt(long, conflicts_with = "batch")]
+    count_tweets: bool,
+
+    /// With `ts count`, print the text's length, counted like `--count`,
+    /// instead of how many tweets it takes
+    #[structopt(long, conflicts_with = "count-tweets")]
+    length: bool,
+
+    /// The text to tweetify, if not read from elsewhere. Several words are joined with spaces,
+    /// like `echo`, so `ts here is my text` needs no quotes. With `--files`, the files to split
+    #[structopt()]
+    words: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Auto,
+    Human,
+    Plain,
+    Json,
+    Jsonl,
+    Csv,
+    Yaml,
+}
+
+impl OutputFormat {
+    fn resolve(self, is_terminal: bool) -> Self {
+        match self {
+            OutputFormat::Auto if is_terminal => OutputFormat::Human,
+            OutputFormat::Auto => OutputFormat::Plain,
+            format => format,
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(OutputFormat::Auto),
+            "human" => Ok(OutputFormat::Human),
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" => Ok(OutputFormat::Yaml),
+            _ => Err(format!(
+                "unknown format `{}`, expected auto, human, plain, json, jsonl, csv, or yaml",
+                s
+            )),
+        }
+    }
+}
+
+// how `--a11y` treats what reads badly with a screen reader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum A11y {
+    Warn,
+    Strict,
+}
+
+impl std::str::FromStr for A11y {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(A11y::Warn),
+            "strict" => Ok(A11y::Strict),
+            _ => Err(format!("unknown level `{}`, expected warn or strict", s)),
+        }
+    }
+}
+
+// how `--format plain` and `ts join` escape each tweet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Escape {
+    None,
+    Shell,
+    Json,
+}
+
+impl Escape {
+    fn apply(self, tweet: &str) -> String {
+        match self {
+            Escape::None => tweet.to_string(),
+            Escape::Shell => tweet
+                .replace('\n', "\\n")
+                .replace("'", "\\'")
+                .replace("\"", "\\\""),
+            Escape::Json => Json::String(tweet.to_string()).to_string(),
+        }
+    }
+
+    // the tweet `escaped` was printed from
+    fn undo(self, escaped: &str) -> Result<String, Box<dyn Error>> {
+        match self {
+            Escape::None => Ok(escaped.to_string()),
+            Escape::Shell => Ok(escaped
+                .replace("\\n", "\n")
+                .replace("\\'", "'")
+                .replace("\\\"", "\"")),
+            Escape::Json => Json::parse(escaped)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("`{}` is not a JSON string", escaped).into()),
+        }
+    }
+}
+
+impl std::str::FromStr for Escape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Escape::None),
+            "shell" => Ok(Escape::Shell),
+            "json" => Ok(Escape::Json),
+            _ => Err(format!(
+                "unknown escaping `{}`, expected none, shell, or json",
+                s
+            )),
+        }
+    }
+}
+
+const FILTERS: [&str; 10] = [
+    "trim",
+    "newlines",
+    "html",
+    "comments",
+    "substitute",
+    "link-last",
+    "dedupe-links",
+    "bullets",
+    "styles",
+    "camel-hashtags",
+];
+
+// the level of a heading marker like `##`
+fn parse_heading(heading: &str) -> Result<usize, String> {
+    match heading.trim() {
+        marker if !marker.is_empty() && marker.chars().all(|c| c == '#') => Ok(marker.len()),
+        _ => Err(format!("expected a heading like `##`, got `{}`", heading)),
+    }
+}
+
+fn parse_definition(definition: &str) -> Result<(String, String), String> {
+    match definition.find('=') {
+        Some(equals) => Ok((
+            definition[..equals].to_string(),
+            definition[equals + 1..].to_string(),
+        )),
+        None => Err(format!(
+            "expected `key=value`, but `{}` has no `=`",
+            definition
+        )),
+    }
+}
+
+/// Runs `ts` on the process's own arguments, stdin and stdout,
+/// printing any error to stderr and exiting with status 1.
+/// A panic writes a bug report, which `ts --report` prints.
+pub fn main() {
+    report::install();
+    let (stdin, stdout) = (io::stdin(), io::stdout());
+    let io = Io {
+        interactive: stdout.is_terminal(),
+        stdin: &mut stdin.lock(),
+        stdout: &mut stdout.lock(),
+    };
+
+    match run(std::env::args_os(), io) {
+        Ok(()) => (),
+        // the reader went away, as with `ts big.txt | head`, so there is nobody left to tell
+        Err(error) if is_broken_pipe(&*error) => (),
+        Err(error) => {
+            if let Some(error) = error.downcast_ref::<clap::Error>() {
+                // usage errors and `--help`, which clap prints and exits for
+                error.exit();
+            } else if let Some(failure) = error.downcast_ref::<Failure>() {
+                print_error(&failure.error, &failure.notes);
+            } else if let Some(error) = error.downcast_ref::<TweetSplitError>() {
+                print_error(error, &[]);
+            } else {
+                eprintln!("error: {}", error);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_error(error: &TweetSplitError, notes: &[String]) {
+    eprintln!("error[{}]: {}", error.code(), error);
+    for note in notes {
+        eprintln!("  = {}", note);
+    }
+    eprintln!(
+        "\nFor more about this error, try `ts explain {}`.",
+        error.code()
+    );
+}
+
+// a split that failed, with notes on where and how to fix it
+#[derive(Debug)]
+struct Failure {
+    error: TweetSplitError,
+    notes: Vec<String>,
+}
+
+impl Failure {
+    fn new(error: TweetSplitError, input: &str, max_tweet_length: usize) -> Self {
+        let mut notes = vec![];
+
+        if let TweetSplitError::WordTooLong {
+            word,
+            offset,
+            length,
+            budget,
+        } = &error
+        {
+            let line = input[..*offset].matches('\n').count() + 1;
+            let shown = match word.char_indices().nth(40) {
+                Some((end, _)) => format!("{}…", &word[..end]),
+                None => word.clone(),
+            };
+
+            notes.push(format!("note: the word is on line {}: '{}'", line, shown));
+            notes.push(format!(
+                "help: raise the limit to fit it with `-l {}`",
+                max_tweet_length + length - budget
+            ));
+            if *budget < max_tweet_length {
+                notes.push("help: or shorten the decorations that take up the rest".to_string());
+            }
+            notes.push("help: or split it across tweets with `--overflow split`".to_string());
+        }
+
+        Self { error, notes }
+    }
+
+    fn in_draft(mut self, name: &str) -> Self {
+        self.notes.insert(0, format!("note: in draft `{}`", name));
+        self
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl Error for Failure {}
+
+/// Where [`run`] reads its input and writes its output, so that it can run in-process.
+pub struct Io<'a> {
+    pub stdin: &'a mut dyn Read,
+    pub stdout: &'a mut dyn Write,
+    /// Whether stdout is a terminal, which `--format auto` prints for
+    pub interactive: bool,
+}
+
+// `ts explain TS001` prints the long explanation of an error code
+fn explain(code: Option<String>, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let code = code.ok_or_else(|| {
+        format!(
+            "`ts explain` needs an error code, one of {}",
+            explain::CODES.join(", ")
+        )
+    })?;
+    let explanation =
+        explain::explain(&code).ok_or_else(|| format!("`{}` is not an error code", code))?;
+
+    writeln!(out, "{}", explanation)?;
+
+    Ok(())
+}
+
+/// Manage the config file and its profiles
+#[derive(Debug, StructOpt)]
+struct ConfigCommand {
+    /// The config file, instead of `$TS_CONFIG` or `~/.config/ts/config.toml`
+    #[structopt(long, global = true, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Debug, StructOpt)]
+enum ConfigAction {
+    /// Print the profiles in the config file
+    Show {
+        /// Print each profile with the options it inherits, as `--profile` uses it
+        #[structopt(long)]
+        resolved: bool,
+
+        /// Print only this profile
+        #[structopt(long)]
+        profile: Option<String>,
+    },
+    /// Print an option of a profile, with what it inherits, like `ts config get profile.rant.numbering`
+    Get { key: String },
+    /// Set an option of a profile, like `ts config set profile.rant.numbering slash`,
+    /// checking that ts accepts it first. Values are TOML, or else plain strings
+    Set { key: String, value: String },
+    /// Open the config file in `$VISUAL` or `$EDITOR`, then check it
+    Edit,
+    /// Print where the config file is, whether or not it exists yet
+    Path,
+}
+
+/// Check the config file and what ts needs from the system, and say how to fix any problems.
+/// Nothing is sent anywhere
+#[derive(Debug, StructOpt)]
+struct Doctor {
+    /// The config file, instead of `$TS_CONFIG` or `~/.config/ts/config.toml`
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+}
+
+/// Join tweets printed with `--format plain`, one a line or between separators, back into the text
+/// they were split from
+#[derive(Debug, StructOpt)]
+struct Join {
+    /// How the tweets were escaped, to undo: none, shell, or json
+    #[structopt(long, default_value = "none")]
+    escape: Escape,
+
+    /// What the tweets were separated by, if not newlines
+    #[structopt(long)]
+    separator: Option<String>,
+
+    /// The counter style the tweets were numbered with, to take off each one
+    #[structopt(long)]
+    numbering: Option<NumberingStyle>,
+
+    /// Where the counter went: prefix or suffix
+    #[structopt(long, default_value = "suffix")]
+    numbering_position: NumberingPosition,
+}
+
+// what `ts` does with its text, which subcommands other than `split` can change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Split,
+    Count,
+    Check,
+}
+
+// `ts config show --resolved` and the rest manage the profiles of the config file
+fn config_command(command: ConfigCommand, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let path = config::Config::path(command.config.as_deref())?;
+
+    match command.action {
+        ConfigAction::Show { resolved, profile } => {
+            let config = config::Config::load(Some(&path))?;
+            let names = match profile {
+                Some(name) => vec![config.profile(&name).map(|_| name)?],
+                None => config.profiles.keys().cloned().collect(),
+            };
+
+            writeln!(out, "# {}", config.path.display())?;
+            for name in names {
+                let profile = if resolved {
+                    config.resolve(&name)?
+                } else {
+                    config.profiles[&name].clone()
+                };
+                write!(out, "\n{}", profile.toml(&name))?;
+            }
+        }
+        ConfigAction::Get { key } => {
+            let (profile, key) = config_key(&key)?;
+            let config = config::Config::load(Some(&path))?;
+            let value = config
+                .resolve(profile)?
+                .get(key)
+                .cloned()
+                .ok_or_else(|| format!("profile `{}` doesn't set `{}`", profile, key))?;
+
+            writeln!(out, "{}", value)?;
+        }
+        ConfigAction::Set { key, value } => {
+            let (profile, key) = config_key(&key)?;
+            let value = config::parse_value(&value)
+                .unwrap_or_else(|_| config::Value::String(value.to_string()));
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(error) => return Err(error.into()),
+            };
+
+            let source = config::set(&source, profile, key, &value);
+            let mut config = config::Config::parse(&source)?;
+            config.path = path.clone();
+            check_profiles(&config)?;
+
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            fs::write(&path, source)?;
+        }
+        ConfigAction::Edit => {
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            let status = Command::new(&editor).arg(&path).status()?;
+            if !status.success() {
+                return Err(format!("{} exited with {}", editor, status).into());
+            }
+
+            check_profiles(&config::Config::load(Some(&path))?)?;
+        }
+        ConfigAction::Path => writeln!(out, "{}", path.display())?,
+    }
+
+    Ok(())
+}
+
+// the profile and option of a key like `profile.rant.numbering`
+fn config_key(key: &str) -> Result<(&str, &str), String> {
+    key.strip_prefix("profile.")
+        .and_then(|key| key.split_once('.'))
+        .filter(|(profile, option)| !profile.is_empty() && !option.is_empty())
+        .ok_or_else(|| format!("expected a key like `profile.name.option`, got `{}`", key))
+}
+
+// check that ts accepts the options of every profile
+fn check_profiles(config: &config::Config) -> Result<(), Box<dyn Error>> {
+    for name in config.profiles.keys() {
+        let args = std::iter::once("ts".to_string()).chain(config.resolve(name)?.args(|_| false));
+        Options::from_iter_safe(args).map_err(|error| in_profile(name, config, error))?;
+    }
+
+    Ok(())
+}
+
+// `error` from parsing the options of profile `name`
+fn in_profile(name: &str, config: &config::Config, error: clap::Error) -> Box<dyn Error> {
+    let message = error.message.lines().next().unwrap_or("");
+
+    format!(
+        "in profile `{}` of {}: {}",
+        name,
+        config.path.display(),
+        message.trim_start_matches("error: ")
+    )
+    .into()
+}
+
+fn is_broken_pipe(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|error| error.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Runs `ts` with the command line `args`, starting with the program name.
+///
+/// Usage errors and `--help` come back as a `clap::Error` for the caller to print.
+pub fn run<I>(args: I, io: Io) -> Result<(), Box<dyn Error>>
+where
+    I: IntoIterator,
+    I::Item: Into<OsString>,
+{
+    let args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    let (action, mut options) = match subcommand(&args)? {
+        Subcommand::Split(options) => (Action::Split, options),
+        Subcommand::Count(options) => (Action::Count, options),
+        Subcommand::Check(options) => (Action::Check, options),
+        // ts can't post, so announcing prints the thread as `ts split` would
+        Subcommand::Announce(options) => {
+            if !options.from_git && options.changelog.is_none() {
+                return Err("`ts announce` needs --from-git or --changelog".into());
+            }
+            (Action::Split, options)
+        }
+        Subcommand::Join(join) => return join_tweets(&join, io.stdin, io.stdout),
+        Subcommand::Post => {
+            return Err(
+                "ts can't post yet, so split with `ts split` and post the tweets yourself".into(),
+            )
+        }
+        Subcommand::Explain { code } => return explain(code, io.stdout),
+        Subcommand::Config(command) => return config_command(command, io.stdout),
+        Subcommand::Doctor(doctor) => {
+            return doctor::run(doctor.config.as_deref(), io.interactive, io.stdout)
+        }
+    };
+
+    options.format = options.format.resolve(io.interactive);
+    if options.null {
+        options.format = OutputFormat::Plain;
+    }
+    // `ts check` fails on what `ts split` warns about
+    options.strict |= action == Action::Check && options.validate.is_some();
+    if options.length && action != Action::Count {
+        return Err("--length is only for `ts count`".into());
+    }
+    // `ts count` counts tweets unless asked for the length
+    options.count_tweets |= action == Action::Count && !options.length;
+
+    if options.report {
+        write!(io.stdout, "{}", report::last()?)?;
+        return Ok(());
+    }
+
+    if options.self_test {
+        return self_test(io.stdout);
+    }
+
+    if let Some(path) = &options.shorten {
+        options.shortener = Some(Shortener::parse(&fs::read_to_string(path)?)?);
+    }
+    if let Some(path) = &options.hashtag_words {
+        options.hashtag_word_list = fs::read_to_string(path)?
+            .lines()
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+    }
+
+    // the positional words are the input and definitions can hold secrets,
+    // so both stay out of reports
+    report::record_options(format!(
+        "{:?}",
+        Options {
+            words: vec![],
+            definitions: options
+                .definitions
+                .iter()
+                .map(|(key, _)| (key.clone(), "<redacted>".to_string()))
+                .collect(),
+            shortener: None,
+            hashtag_word_list: HashSet::new(),
+            ..options.clone()
+        }
+    ));
+    let mut split_options = split_options(&options)?;
+    let pipeline = pipeline(&options);
+
+    if options.offline {
+        check_offline(&options)?;
+    }
+
+    if options.qr && !cfg!(feature = "qr") {
+        return Err("--qr needs ts built with the `qr` feature".into());
+    }
+
+    if options.healthcheck {
+        return healthcheck(&split_options, io.stdout);
+    }
+
+    let mut out = BufWriter::new(io.stdout);
+
+    if let Some(batch) = &options.batch {
+        if action != Action::Split {
+            return Err("`ts count` and `ts check` take one text, not a --batch".into());
+        }
+        split_batch(&options, &split_options, &pipeline, batch, &mut out)?;
+    } else if options.files {
+        if action != Action::Split {
+            return Err("`ts count` and `ts check` take one text, not --files".into());
+        }
+        split_files(&options, &split_options, &pipeline, &mut out)?;
+    } else {
+        let input = pipeline.apply(read_input(&options, io.stdin)?)?;
+        report::record_input(&input);
+        check_confusables(&options, &input);
+        segment_sms(&options, &mut split_options, &input);
+
+        if options.length {
+            writeln!(out, "{}", split_options.count.count(input.trim()))?;
+            out.flush()?;
+            return Ok(());
+        }
+
+        if action == Action::Split && streams(&options) {
+            stream_thread(&options, &split_options, &input, &mut out)?;
+            out.flush()?;
+            return Ok(());
+        }
+
+        let mut thread = split_thread(&options, &input, &split_options)
+            .map_err(|error| Failure::new(error, &input, split_options.max_tweet_length))?;
+        vary_duplicates(&options, &split_options, &mut thread);
+        validate(&options, &thread)?;
+        check_a11y(&options, &thread)?;
+
+        if options.count_tweets {
+            writeln!(out, "{}", thread.tweets.len())?;
+        } else if action == Action::Check {
+            check_fits(&split_options, &thread, &mut out)?;
+        } else if let Some(dir) = &options.out_dir {
+            write_tweet_files(dir, &thread)?;
+        } else {
+            print_thread(&options, &split_options, &thread, &mut out)?;
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+// for `--out-dir`, each tweet in a file named for its number, padded to at least 3 digits
+// so the files sort in order. files an earlier, longer thread left, padded to the same width
+// and numbered past this one, are removed so they aren't posted with it. anything else,
+// like a `2024.txt` or `7.txt` beside a thread of 3, stays
+fn write_tweet_files(dir: &Path, thread: &Thread) -> io::Result<()> {
+    let total = thread.tweets.len();
+    let width = total.to_string().len().max(3);
+    fs::create_dir_all(dir)?;
+
+    for (index, tweet) in thread.tweets.iter().enumerate() {
+        let path = dir.join(format!("{:0width$}.txt", index + 1, width = width));
+        fs::write(path, tweet)?;
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let number = match name.to_str().and_then(|name| name.strip_suffix(".txt")) {
+            Some(number) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+                number
+            }
+            _ => continue,
+        };
+        let stale =
+            number.len() == width && number.parse::<usize>().is_ok_and(|number| number > total);
+
+        if stale && entry.file_type()?.is_file() {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+// for `ts check`, fail unless `thread` is one tweet
+fn check_fits(
+    split_options: &SplitOptions,
+    thread: &Thread,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let max = split_options.max_tweet_length;
+
+    match thread.tweets.as_slice() {
+        [tweet] => {
+            writeln!(out, "ok: {} of {}", split_options.count.count(tweet), max)?;
+            Ok(())
+        }
+        tweets => {
+            let text = thread.texts.join(" ");
+            Err(format!(
+                "the text is {} long and takes {} tweets of {}",
+                split_options.count.count(&text),
+                tweets.len(),
+                max
+            )
+            .into())
+        }
+    }
+}
+
+// `ts join`, undoing `--format plain`'s separators, escapes, and any counters
+fn join_tweets(
+    join: &Join,
+    stdin: &mut dyn Read,
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut input = String::new();
+    stdin.read_to_string(&mut input)?;
+
+    let lines = match &join.separator {
+        Some(separator) => input.split(separator.as_str()).collect::<Vec<&str>>(),
+        None => input.lines().collect(),
+    };
+    let lines = lines
+        .into_iter()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<&str>>();
+    let total = lines.len();
+    let mut texts = vec![];
+
+    for (index, line) in lines.into_iter().enumerate() {
+        let unescaped = join.escape.undo(line.trim())?;
+        let mut text = unescaped.trim();
+
+        if let Some(style) = join.numbering {
+            let counter = Numbering::new(style, join.numbering_position).render(index + 1, total);
+            text = match join.numbering_position {
+                NumberingPosition::Prefix => text.strip_prefix(counter.as_str()),
+                NumberingPosition::Suffix => text.strip_suffix(counter.as_str()),
+            }
+            .unwrap_or(text);
+        }
+
+        texts.push(text.to_string());
+    }
+
+    writeln!(out, "{}", texts.join(" "))?;
+
+    Ok(())
+}
+
+// the subcommand of `args`, `split` without one, with its options on top of those from
+// `--profile`
+fn subcommand(args: &[OsString]) -> Result<Subcommand, Box<dyn Error>> {
+    let matches = Ts::clap().get_matches_from_safe(args)?;
+    // the matches of the subcommand's own options, or of `ts`'s without one
+    let own = matches.subcommand().1.unwrap_or(&matches);
+    let mut subcommand = Ts::from_clap(&matches).into_subcommand();
+
+    let options = match &mut subcommand {
+        Subcommand::Split(options)
+        | Subcommand::Count(options)
+        | Subcommand::Check(options)
+        | Subcommand::Announce(options) => options,
+        Subcommand::Config(command) => {
+            // clap 2 doesn't pass a global flag given after a nested subcommand,
+            // as in `ts config show --config path`, back up to `ts config`
+            if command.config.is_none() {
+                command.config = own
+                    .subcommand()
+                    .1
+                    .and_then(|action| action.value_of_os("config"))
+                    .map(PathBuf::from);
+            }
+            return Ok(subcommand);
+        }
+        _ => return Ok(subcommand),
+    };
+    let name = match &options.profile {
+        Some(name) => name,
+        None => return Ok(subcommand),
+    };
+    let config = config::Config::load(options.config.as_deref())?;
+    let profile = config
+        .resolve(name)?
+        .args(|flag| own.occurrences_of(flag) > 0);
+
+    // after the command line's own flags, but before a `--` that ends them
+    let end = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let mut args = args.to_vec();
+    args.splice(end..end, profile.into_iter().map(OsString::from));
+
+    Ts::from_iter_safe(&args)
+        .map(Ts::into_subcommand)
+        .map_err(|error| in_profile(name, &config, error))
+}
+
+// with `--platform sms`, count and size tweets as the segments of an SMS of `input`
+fn segment_sms(options: &Options, split_options: &mut SplitOptions, input: &str) {
+    let sms = options.platform.is_some_and(|preset| preset.name == "sms");
+
+    if sms && options.max_tweet_length.is_none() && options.count.is_none() {
+        sms::segment_options(input, split_options);
+    }
+}
+
+// split with `--promote-headings` or `--code-fences`, and by `--numbering-scope` section,
+// if need be
+fn split_thread(
+    options: &Options,
+    input: &str,
+    split_options: &SplitOptions,
+) -> Result<Thread, TweetSplitError> {
+    let split = |input: &str, split_options: &SplitOptions| match &options.promote_headings {
+        Some(style) => sections::split_promoting_headings(input, split_options, style),
+        None if keeps_code_fences(options) => fences::split_thread(input, split_options),
+        None => tweet_split_core::split_thread(input, split_options),
+    };
+
+    match options.numbering_scope {
+        NumberingScope::Thread => split(input, split_options),
+        NumberingScope::Section => sections::split_thread_with(input, split_options, split),
+    }
+}
+
+// whether code fences are closed and reopened across tweets, with `--code-fences` or for Discord
+fn keeps_code_fences(options: &Options) -> bool {
+    options.code_fences
+        || options
+            .platform
+            .is_some_and(|preset| preset.name == "discord")
+}
+
+// whether tweets can be printed as they're split, rather than after the whole thread is:
+// plain output, and nothing that splits differently or needs every tweet before printing
+fn streams(options: &Options) -> bool {
+    options.format == OutputFormat::Plain
+        && !options.count_tweets
+        && !options.content_hash
+        && !options.stats
+        && !options.vary_duplicates
+        && options.out_dir.is_none()
+        && options.validate.is_none()
+        && options.a11y.is_none()
+        && options.promote_headings.is_none()
+        && options.numbering_scope == NumberingScope::Thread
+        && !keeps_code_fences(options)
+}
+
+// print each tweet of `input` as soon as it is split, flushing after each. the core still
+// splits the whole thread first when a decoration, like a counter, depends on the total
+fn stream_thread(
+    options: &Options,
+    split_options: &SplitOptions,
+    input: &str,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let tweets = tweet_split_core::split_text_iter(input, split_options);
+
+    for (index, tweet) in tweets.enumerate() {
+        let tweet =
+            tweet.map_err(|error| Failure::new(error, input, split_options.max_tweet_length))?;
+        print_plain(options, index, &tweet, out)?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+fn read_input(options: &Options, stdin: &mut dyn Read) -> Result<String, Box<dyn Error>> {
+    let input = if let Some(changelog) = &options.changelog {
+        let version = match (&options.changelog_version, options.from_git) {
+            (Some(version), _) => version.clone(),
+            (None, true) => latest_tag()?,
+            (None, false) => {
+                return Err("--changelog needs --changelog-version or --from-git".into());
+            }
+        };
+
+        let changelog = fs::read_to_string(changelog)?;
+        let section =
+            changelog::extract_section_at(&changelog, &version, options.changelog_heading)
+                .ok_or_else(|| format!("the changelog has no section for {}", version))?;
+
+        changelog::render_section(section, options.bullets)
+    } else if let Some(input_location) = &options.input_path {
+        fs::read_to_string(input_location)?
+    } else if options.from_git {
+        latest_tag_message()?
+    } else if !options.words.is_empty() {
+        options.words.join(" ")
+    } else {
+        let mut buf = String::new();
+        stdin.read_to_string(&mut buf)?;
+        buf
+    };
+
+    Ok(input)
+}
+
+// the filters to run on the input, from `--filter`, `-c`, `-s`, and `--shorten`
+fn pipeline(options: &Options) -> Pipeline {
+    let mut pipeline = Pipeline::new();
+
+    for name in &options.filters {
+        pipeline.push(filter(options, name));
+    }
+    if options.strip_comments && !pipeline.contains("comments") {
+        pipeline.push(filter(options, "comments"));
+    }
+    if (options.substitute || !options.definitions.is_empty()) && !pipeline.contains("substitute") {
+        pipeline.push(filter(options, "substitute"));
+    }
+    let camel_hashtags = !options.camel_hashtags.is_empty() || options.hashtag_words.is_some();
+    if camel_hashtags && !pipeline.contains("camel-hashtags") {
+        pipeline.push(filter(options, "camel-hashtags"));
+    }
+    if let Some(shortener) = &options.shortener {
+        pipeline.push(Box::new(shortener.clone()));
+    }
+
+    if pipeline.contains("styles") {
+        eprintln!(
+            "warning: --filter styles writes letters that screen readers read as math symbols or skip"
+        );
+    }
+
+    pipeline
+}
+
+// the filter called `name`, one of `FILTERS`
+fn filter(options: &Options, name: &str) -> Box<dyn TextFilter> {
+    match name {
+        "trim" => Box::new(preprocess::Trim),
+        "newlines" => Box::new(preprocess::NormalizeNewlines),
+        "html" => Box::new(preprocess::StripHtml),
+        "comments" => Box::new(preprocess::StripComments),
+        "link-last" => Box::new(preprocess::LinkLast),
+        "dedupe-links" => Box::new(preprocess::DedupeLinks),
+        "bullets" => Box::new(preprocess::ListBullets {
+            bullet: options.list_bullet.clone(),
+            keycaps: options.list_keycaps,
+        }),
+        "styles" => Box::new(preprocess::StyleEmphasis),
+        "camel-hashtags" => Box::new(preprocess::CamelHashtags {
+            hashtags: options.camel_hashtags.clone(),
+            words: options.hashtag_word_list.clone(),
+        }),
+        "substitute" => Box::new(preprocess::SubstituteVariables {
+            definitions: options.definitions.iter().cloned().collect(),
+            environment: true,
+        }),
+        _ => unreachable!("`{}` is not one of the filters", name),
+    }
+}
+
+fn split_options(options: &Options) -> Result<SplitOptions, Box<dyn Error>> {
+    let max_tweet_length = options
+        .max_tweet_length
+        .or(options.platform.map(|preset| preset.max_length))
+        .unwrap_or(280);
+    let mut split_options = SplitOptions::new(max_tweet_length);
+    split_options.count = options
+        .count
+        .or(options.platform.map(|preset| preset.count))
+        .unwrap_or_default();
+    split_options.prefix = options.prefix.clone();
+    split_options.suffix = options.suffix.clone();
+    if let Some(template) = &options.template {
+        split_options.prefix = Some(template.prefix.clone());
+        split_options.suffix = Some(template.suffix.clone());
+    }
+    split_options.today = Some(today()?);
+    split_options.paragraphs = options.paragraphs;
+    split_options.keep_together = options.keep_together.clone();
+    split_options.overflow = options.overflow;
+    split_options.strategy = options.strategy;
+    if options.keep_initials {
+        split_options
+            .keep_together
+            .push(tweet_split_core::glue::initials());
+    }
+    if options.changelog.is_some() && options.bullets == Bullets::Keep {
+        split_options
+            .keep_together
+            .push(tweet_split_core::glue::list_items());
+    }
+    if options.keep_units {
+        split_options
+            .keep_together
+            .push(tweet_split_core::glue::numbers_with_units());
+    }
+    split_options.sign_off = match (options.sign_off_lines, &options.sign_off_delimiter) {
+        (Some(lines), _) => Some(SignOff::Lines(lines)),
+        (None, Some(delimiter)) => Some(SignOff::Delimiter(delimiter.clone())),
+        (None, None) => None,
+    };
+    if options.end_marker {
+        split_options.end_marker = Some(options.end_marker_text.clone());
+    }
+    split_options.numbering = options.numbering.map(|style| {
+        let mut numbering = Numbering::new(style, options.numbering_position);
+        numbering.positions = options.number_on;
+        numbering
+    });
+    if !options.reply_mentions.is_empty() {
+        split_options.reply_mentions = Some(ReplyMentions::new(
+            &options.reply_mentions,
+            options.render_reply_mentions,
+        ));
+    }
+    if options.word_joiner {
+        split_options.decorators.push(Arc::new(WordJoiner));
+    }
+    if options.escape_mentions {
+        split_options.decorators.push(Arc::new(EscapeMentions));
+    }
+    if options.ellipsis || options.leading_ellipsis {
+        split_options.decorators.push(Arc::new(Ellipsis {
+            trailing: options.ellipsis,
+            leading: options.leading_ellipsis,
+            ..Ellipsis::default()
+        }));
+    }
+    if !options.hashtags.is_empty() {
+        split_options.decorators.push(Arc::new(Hashtags::new(
+            &options.hashtags,
+            options.hashtags_on,
+        )));
+    }
+
+    Ok(split_options)
+}
+
+// split every draft in a JSON Lines or CSV file as its own thread
+fn split_batch(
+    options: &Options,
+    split_options: &SplitOptions,
+    pipeline: &Pipeline,
+    path: &Path,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let source = fs::read_to_string(path)?;
+    let drafts = if path.extension().is_some_and(|extension| extension == "csv") {
+        batch::parse_csv(&source)?
+    } else {
+        batch::parse_jsonl(&source)?
+    };
+
+    let mut ignored_fields = vec![];
+
+    for (draft_number, draft) in drafts.iter().enumerate() {
+        let mut draft_options = split_options.clone();
+        let mut name = format!("draft {}", draft_number + 1);
+
+        for (field, value) in &draft.fields {
+            match field.as_str() {
+                "name" if !value.is_empty() => name = value.clone(),
+                "name" => (),
+                "max_length" => draft_options.max_tweet_length = value.parse()?,
+                "prefix" => draft_options.prefix = Some(Template::parse(value)?),
+                "suffix" => draft_options.suffix = Some(Template::parse(value)?),
+                "numbering" => {
+                    let mut numbering = Numbering::new(value.parse()?, options.numbering_position);
+                    numbering.positions = options.number_on;
+                    draft_options.numbering = Some(numbering);
+                }
+                "end_marker" => draft_options.end_marker = Some(value.clone()),
+                _ if !ignored_fields.contains(field) => {
+                    eprintln!("ignoring unsupported draft field `{}`", field);
+                    ignored_fields.push(field.clone());
+                }
+                _ => (),
+            }
+        }
+
+        split_named(
+            options,
+            &draft_options,
+            pipeline,
+            &name,
+            &draft.text,
+            draft_number,
+            out,
+        )?;
+    }
+
+    Ok(())
+}
+
+// `--files`, splitting each file the positional arguments name or match as its own thread
+fn split_files(
+    options: &Options,
+    split_options: &SplitOptions,
+    pipeline: &Pipeline,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if options.words.is_empty() {
+        return Err("--files needs at least one file".into());
+    }
+
+    let mut paths = vec![];
+    for pattern in &options.words {
+        let matches = glob(pattern)?;
+        if matches.is_empty() {
+            return Err(format!("no files match `{}`", pattern).into());
+        }
+        paths.extend(matches);
+    }
+
+    for (number, path) in paths.iter().enumerate() {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+        let name = path.display().to_string();
+        split_named(options, split_options, pipeline, &name, &text, number, out)?;
+    }
+
+    Ok(())
+}
+
+// the 0-based `number`th of several texts, under a header with its `name`
+fn split_named(
+    options: &Options,
+    split_options: &SplitOptions,
+    pipeline: &Pipeline,
+    name: &str,
+    text: &str,
+    number: usize,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let text = pipeline.apply(text.to_string())?;
+    report::record_input(&text);
+    check_confusables(options, &text);
+    let mut thread = split_thread(options, &text, split_options).map_err(|error| {
+        Failure::new(error, &text, split_options.max_tweet_length).in_draft(name)
+    })?;
+    vary_duplicates(options, split_options, &mut thread);
+    validate(options, &thread).map_err(|failure| failure.in_draft(name))?;
+    check_a11y(options, &thread).map_err(|failure| failure.in_draft(name))?;
+
+    if number > 0 {
+        writeln!(out)?;
+    }
+    writeln!(out, "==> {} <==", name)?;
+    print_thread(options, split_options, &thread, out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+// the files `pattern` names, sorted, where `*` and `?` in its last component match
+// any run of characters and any one character; a pattern without them is the path itself
+fn glob(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.contains(['*', '?']) => name,
+        _ => return Ok(vec![path.to_path_buf()]),
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let matches = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|file| wildcard_match(name, file));
+        if matches && entry.file_type()?.is_file() {
+            paths.push(path.with_file_name(entry.file_name()));
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+// whether `name` matches `pattern`, with `*` for any run of characters and `?` for any one
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+    // matched[j]: whether the pattern so far matches the first j characters of the name
+    let mut matched = vec![false; name.len() + 1];
+    matched[0] = true;
+
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matched[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matched[j - 1],
+                c => j > 0 && matched[j - 1] && name[j - 1] == c,
+            };
+        }
+        matched = next;
+    }
+
+    matched[name.len()]
+}
+
+// with `--check-confusables`, warn about lookalike and invisible characters in the input
+fn check_confusables(options: &Options, input: &str) {
+    if !options.check_confusables {
+        return;
+    }
+
+    for suspicion in confusables::check(input) {
+        let line = input[..suspicion.offset()].matches('\n').count() + 1;
+        eprintln!("warning: line {}: {}", line, suspicion);
+    }
+}
+
+// with `--vary-duplicates`, make tweets that repeat earlier ones differ invisibly
+fn vary_duplicates(options: &Options, split_options: &SplitOptions, thread: &mut Thread) {
+    if options.vary_duplicates {
+        validate::vary_duplicates(
+            &mut thread.tweets,
+            split_options.max_tweet_length,
+            split_options.count,
+        );
+    }
+}
+
+// warn about, or with `--strict` fail on, tweets that break the rules of `--validate`
+fn validate(options: &Options, thread: &Thread) -> Result<(), Failure> {
+    let platform = match options.validate {
+        Some(platform) => platform,
+        None => return Ok(()),
+    };
+
+    let violations = validate::check_thread(&thread.tweets, &Rules::for_platform(platform));
+    let notes = violations
+        .iter()
+        .map(|(index, violation)| format!("tweet {} has {}", index + 1, violation))
+        .collect::<Vec<String>>();
+
+    if !options.strict || notes.is_empty() {
+        for note in notes {
+            eprintln!("warning: {}", note);
+        }
+        return Ok(());
+    }
+
+    let mut tweets = violations
+        .iter()
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    tweets.dedup();
+
+    Err(Failure {
+        error: TweetSplitError::RuleViolation {
+            details: format!(
+                "{} of {} tweets break {:?}'s rules.",
+                tweets.len(),
+                thread.tweets.len(),
+                platform
+            ),
+        },
+        notes: notes
+            .into_iter()
+            .map(|note| format!("note: {}", note))
+            .collect(),
+    })
+}
+
+// with `--a11y`, warn about, or with `--a11y strict` fail on, tweets that read badly
+// with a screen reader
+fn check_a11y(options: &Options, thread: &Thread) -> Result<(), Failure> {
+    let level = match options.a11y {
+        Some(level) => level,
+        None => return Ok(()),
+    };
+
+    let issues = a11y::check_thread(&thread.tweets);
+    let notes = issues
+        .iter()
+        .map(|(index, issue)| format!("tweet {} has {}", index + 1, issue))
+        .collect::<Vec<String>>();
+
+    if level == A11y::Warn || notes.is_empty() {
+        for note in notes {
+            eprintln!("warning: {}", note);
+        }
+        return Ok(());
+    }
+
+    let mut tweets = issues.iter().map(|(index, _)| index).collect::<Vec<_>>();
+    tweets.dedup();
+
+    Err(Failure {
+        error: TweetSplitError::RuleViolation {
+            details: format!(
+                "{} of {} tweets read badly with a screen reader.",
+                tweets.len(),
+                thread.tweets.len()
+            ),
+        },
+        notes: notes
+            .into_iter()
+            .map(|note| format!("note: {}", note))
+            .collect(),
+    })
+}
+
+// ts has no network features, so the only way to ask for one is a URL where a
+// path is expected; reject those up front rather than as a missing file
+fn check_offline(options: &Options) -> Result<(), Box<dyn Error>> {
+    let paths = [&options.input_path, &options.batch, &options.changelog];
+    let files = options
+        .words
+        .iter()
+        .map(PathBuf::from)
+        .filter(|_| options.files);
+
+    for path in paths.iter().filter_map(|path| (*path).clone()).chain(files) {
+        let path = path.to_string_lossy();
+
+        if path.contains("://") {
+            return Err(format!("--offline forbids network input, but got `{}`", path).into());
+        }
+    }
+
+    Ok(())
+}
+
+const HEALTHCHECK_SAMPLE: &str = "The quick brown fox jumps over the lazy dog, \
+    and then it does so again, and again, until the dog finally gets up and leaves.";
+
+fn healthcheck(split_options: &SplitOptions, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let splits = tweet_split_core::split_with_options(HEALTHCHECK_SAMPLE, split_options)?;
+
+    if splits.is_empty() {
+        return Err("healthcheck split produced no tweets".into());
+    }
+
+    if let Some(split) = splits
+        .iter()
+        .find(|split| split_options.count.count(split) > split_options.max_tweet_length)
+    {
+        return Err(format!("healthcheck split produced an overlong tweet: {:?}", split).into());
+    }
+
+    writeln!(out, "ok")?;
+
+    Ok(())
+}
+
+// what a self-test run of ts should do
+enum Expected {
+    Prints(&'static str),
+    FailsWith(&'static str),
+}
+
+// command lines, their stdin, and what they should do
+const SELF_TESTS: &[(&[&str], &str, Expected)] = &[
+    (
+        &["--format", "plain", "-l", "20"],
+        "the quick brown fox jumps over the lazy dog",
+        Expected::Prints("the quick brown fox\njumps over the lazy\ndog\n"),
+    ),
+    (
+        &["--format", "plain", "-l", "20", "-n", "slash"],
+        "the quick brown fox jumps over the lazy dog",
+        Expected::Prints("the quick brown 1/3\nfox jumps over 2/3\nthe lazy dog 3/3\n"),
+    ),
+    (
+        &["--format", "human", "-l", "20", "--end-marker"],
+        "a short thread",
+        Expected::Prints("--- 1/1 (length 19) ---\na short thread /end\n"),
+    ),
+    (
+        &[
+            "--format",
+            "plain",
+            "--count",
+            "graphemes",
+            "-l",
+            "6",
+            "--filter",
+            "html",
+        ],
+        "<p>café&nbsp;au lait</p>",
+        Expected::Prints("café\nau\nlait\n"),
+    ),
+    (
+        &["--format", "plain", "-l", "5"],
+        "incomprehensible",
+        Expected::FailsWith("TS006"),
+    ),
+    (
+        &["--validate", "twitter", "--strict", "-l", "5"],
+        "again again",
+        Expected::FailsWith("TS007"),
+    ),
+];
+
+// `--self-test`: run ts in-process on built-in inputs and check what it does
+fn self_test(out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let mut failures = vec![];
+
+    for (args, stdin, expected) in SELF_TESTS {
+        let mut stdout = vec![];
+        let io = Io {
+            stdin: &mut stdin.as_bytes(),
+            stdout: &mut stdout,
+            interactive: false,
+        };
+        let result = run(std::iter::once("ts").chain(args.iter().copied()), io);
+
+        let code = result.as_ref().err().and_then(|error| {
+            error
+                .downcast_ref::<Failure>()
+                .map(|failure| &failure.error)
+                .or_else(|| error.downcast_ref::<TweetSplitError>())
+                .map(TweetSplitError::code)
+        });
+        let stdout = String::from_utf8_lossy(&stdout);
+
+        let passed = match expected {
+            Expected::Prints(expected) => result.is_ok() && stdout == *expected,
+            Expected::FailsWith(expected) => code == Some(*expected),
+        };
+        if !passed {
+            let outcome = match &result {
+                Ok(()) => format!("printed {:?}", stdout),
+                Err(error) => format!("failed with `{}`", error),
+            };
+            failures.push(format!("`ts {}` {}", args.join(" "), outcome));
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(format!("self-test failed:\n  {}", failures.join("\n  ")).into());
+    }
+
+    writeln!(out, "ok, {} checks passed", SELF_TESTS.len())?;
+
+    Ok(())
+}
+
+// how many tweets to buffer before flushing, so long threads show up as they're written
+const FLUSH_EVERY: usize = 64;
+
+fn print_thread(
+    options: &Options,
+    split_options: &SplitOptions,
+    thread: &Thread,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if options.content_hash {
+        return writeln!(out, "{}", thread.content_hash());
+    }
+    if options.stats {
+        return write!(
+            out,
+            "{}",
+            thread.stats(split_options.count, split_options.max_tweet_length)
+        );
+    }
+
+    let wrap_width = options
+        .wrap_width
+        .or_else(|| Some(terminal::terminal_width().unwrap_or(80)).filter(|_| options.wrap));
+
+    // opened before any tweets, so a thread of none is still an array
+    if options.format == OutputFormat::Json {
+        write!(out, "[")?;
+    }
+    for (index, split) in thread.tweets.iter().enumerate() {
+        if options.format == OutputFormat::Json {
+            let separator = if index == 0 { "" } else { ",\n" };
+            let json = tweet_json(options, split_options, thread, index)?;
+            write!(out, "{}{}", separator, json)?;
+        } else if options.format == OutputFormat::Jsonl {
+            writeln!(
+                out,
+                "{}",
+                tweet_json(options, split_options, thread, index)?
+            )?;
+        } else if options.format == OutputFormat::Csv || options.format == OutputFormat::Yaml {
+            print_record(options, split_options, thread, index, out)?;
+        } else if options.format == OutputFormat::Human {
+            let mut preview = thread.preview(split_options.count).only(index);
+            if options.ids {
+                preview = preview.with_ids();
+            }
+            match wrap_width {
+                Some(width) => write!(out, "{:width$}", preview, width = width)?,
+                None => write!(out, "{}", preview)?,
+            }
+            print_qr(options, split, out)?;
+        } else {
+            print_plain(options, index, split, out)?;
+        }
+
+        if (index + 1) % FLUSH_EVERY == 0 {
+            out.flush()?;
+        }
+    }
+    if options.format == OutputFormat::Json {
+        writeln!(out, "]")?;
+    }
+
+    Ok(())
+}
+
+// the tweet at 0-based `index` as `--format plain` prints it, escaped and separated
+fn print_plain(
+    options: &Options,
+    index: usize,
+    split: &str,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let separator = match &options.separator {
+        Some(separator) => separator.as_str(),
+        None if options.null => "\0",
+        None => "\n",
+    };
+
+    if options.ids {
+        write!(
+            out,
+            "{}\t",
+            tweet_split_core::id::tweet_id(split, index + 1)
+        )?;
+    }
+    write!(out, "{}{}", options.escape.apply(split), separator)?;
+    print_qr(options, split, out)
+}
+
+// the tweet at 0-based `index` as a row of CSV, after a header for the first,
+// or an item of a YAML list
+fn print_record(
+    options: &Options,
+    split_options: &SplitOptions,
+    thread: &Thread,
+    index: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let tweet = &thread.tweets[index];
+    let mut fields = vec![
+        ("index", (index + 1).to_string()),
+        ("total", thread.tweets.len().to_string()),
+        ("length", split_options.count.count(tweet).to_string()),
+    ];
+    if options.ids {
+        fields.push(("id", tweet_split_core::id::tweet_id(tweet, index + 1)));
+    }
+
+    if options.format == OutputFormat::Csv {
+        let csv = |field: &str| format!("\"{}\"", field.replace('"', "\"\""));
+
+        if index == 0 {
+            let names = fields.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+            writeln!(out, "{},tweet", names.join(","))?;
+        }
+        for (_, value) in &fields {
+            write!(out, "{},", value)?;
+        }
+        writeln!(out, "{}", csv(tweet))
+    } else {
+        for (number, (name, value)) in fields.iter().enumerate() {
+            let indent = if number == 0 { "- " } else { "  " };
+            writeln!(out, "{}{}: {}", indent, name, value)?;
+        }
+        // a JSON string is a double-quoted YAML scalar
+        writeln!(out, "  tweet: {}", Json::String(tweet.clone()))
+    }
+}
+
+// the tweet at 0-based `index` as JSON, with a breakdown of its budget
+fn tweet_json(
+    options: &Options,
+    split_options: &SplitOptions,
+    thread: &Thread,
+    index: usize,
+) -> io::Result<Json> {
+    let total = thread.tweets.len();
+    let tweet = &thread.tweets[index];
+    let usage = split_options
+        .budget_usage(index, total, &thread.texts[index])
+        .map_err(io::Error::other)?;
+    let number = |n: usize| Json::Number(n as f64);
+
+    let mut members = vec![
+        ("index".to_string(), number(index + 1)),
+        ("total".to_string(), number(total)),
+        ("text".to_string(), Json::String(tweet.clone())),
+        (
+            "length".to_string(),
+            number(split_options.count.count(tweet)),
+        ),
+    ];
+    if options.ids {
+        members.push((
+            "id".to_string(),
+            Json::String(tweet_split_core::id::tweet_id(tweet, index + 1)),
+        ));
+    }
+    let budget = [
+        ("max_length", usage.max_tweet_length),
+        ("content", usage.content),
+        ("prefix", usage.prefix),
+        ("suffix", usage.suffix),
+        ("numbering", usage.numbering),
+        ("end_marker", usage.end_marker),
+        ("decorators", usage.decorators),
+        ("reserved", usage.reserved),
+        ("unused", usage.unused),
+    ];
+    let entities = entities::extract(tweet, split_options.count)
+        .iter()
+        .map(|entity| {
+            let mut members = vec![
+                (
+                    "type".to_string(),
+                    Json::String(entity.kind.name().to_string()),
+                ),
+                (
+                    "text".to_string(),
+                    Json::String(entity.text(tweet).to_string()),
+                ),
+                ("start".to_string(), number(entity.range.start)),
+                ("end".to_string(), number(entity.range.end)),
+                ("byte_start".to_string(), number(entity.bytes.start)),
+                ("byte_end".to_string(), number(entity.bytes.end)),
+            ];
+            let expanded = options
+                .shortener
+                .as_ref()
+                .filter(|_| entity.kind == EntityKind::Url)
+                .and_then(|shortener| shortener.expand(entity.text(tweet)));
+            if let Some(expanded) = expanded {
+                members.push((
+                    "expanded_url".to_string(),
+                    Json::String(expanded.to_string()),
+                ));
+            }
+
+            Json::Object(members)
+        })
+        .collect();
+    members.push(("entities".to_string(), Json::Array(entities)));
+    members.push((
+        "budget".to_string(),
+        Json::Object(
+            budget
+                .iter()
+                .map(|(name, value)| (name.to_string(), number(*value)))
+                .collect(),
+        ),
+    ));
+
+    Ok(Json::Object(members))
+}
+
+#[cfg(feature = "qr")]
+fn print_qr(options: &Options, split: &str, out: &mut impl Write) -> io::Result<()> {
+    if !options.qr {
+        return Ok(());
+    }
+
+    match qr::QrCode::encode(split) {
+        Some(code) => write!(out, "{}", code.to_terminal()),
+        None => writeln!(out, "(too long for a QR code)"),
+    }
+}
+
+#[cfg(not(feature = "qr"))]
+fn print_qr(_: &Options, _: &str, _: &mut impl Write) -> io::Result<()> {
+    Ok(())
+}
+
+// run git with `args` in the current directory, returning its trimmed stdout
+fn git(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+// the latest tag reachable from HEAD
+fn latest_tag() -> Result<String, Box<dyn Error>> {
+    git(&["describe", "--tags", "--abbrev=0"])
+}
+
+// the subject and body of the latest tag's annotation, without any signature.
+// lightweight tags have no annotation, so this is the tagged commit's message instead.
+fn latest_tag_message() -> Result<String, Box<dyn Error>> {
+    let tag = latest_tag()?;
+
+    git(&[
+        "for-each-ref",
+        &format!("refs/tags/{}", tag),
+        "--format=%(contents:subject)%0a%0a%(contents:body)",
+    ])
+}
+
+// the current date in UTC
+fn today() -> Result<Date, Box<dyn Error>> {
+    let seconds = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs();
+
+    Ok(Date::from_days_since_epoch((seconds / 86_400) as i64))
+}
@@ -0,0 +1,3 @@
+fn main() {
+    tweet_split_cli::main();
+}
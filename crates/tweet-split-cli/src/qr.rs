@@ -0,0 +1,571 @@
+// QR codes drawn in the terminal, for moving tweets to a phone without a network
+//
+// text is encoded in byte mode at error correction level L, in the smallest version
+// (size) that holds it, following ISO/IEC 18004. the layout follows Project Nayuki's
+// well-known reference implementation.
+
+/// Error correction codewords per block at level L, by version
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30,
+    30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+];
+
+/// Error correction blocks at level L, by version
+const ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14,
+    15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+];
+
+// the two format bits for error correction level L
+const LEVEL_L: u32 = 0b01;
+
+/// A QR code as rows of modules, `true` for dark.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCode {
+    pub size: usize,
+    modules: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    /// Encode `text`, or `None` if it's longer than the largest QR code holds.
+    pub fn encode(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let version = (1..=40).find(|version| {
+            let header_bits = 4 + character_count_bits(*version);
+            header_bits + bytes.len() * 8 <= data_codewords(*version) * 8
+        })?;
+
+        let data = data_codewords_for(bytes, version);
+        let codewords = add_error_correction(&data, version);
+
+        let mut code = Grid::new(version);
+        code.draw_function_patterns();
+        code.draw_codewords(&codewords);
+
+        // keep the mask that leaves the fewest patterns that confuse scanners
+        let (_, mask) = (0..8)
+            .map(|mask| {
+                let mut masked = code.clone();
+                masked.apply_mask(mask);
+                masked.draw_format_bits(mask);
+                (masked.penalty(), mask)
+            })
+            .min()
+            .expect("there are eight masks");
+
+        code.apply_mask(mask);
+        code.draw_format_bits(mask);
+
+        Some(Self {
+            size: code.size,
+            modules: code.modules,
+        })
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y][x]
+    }
+
+    /// The code drawn with half blocks, two rows of modules per line, inside a quiet zone.
+    ///
+    /// Light modules are drawn and dark ones left blank,
+    /// which shows correctly on the usual light-on-dark terminal.
+    pub fn to_terminal(&self) -> String {
+        const QUIET_ZONE: usize = 2;
+        let size = self.size as isize;
+        let quiet = QUIET_ZONE as isize;
+        let is_light = |x: isize, y: isize| {
+            x < 0 || y < 0 || x >= size || y >= size || !self.is_dark(x as usize, y as usize)
+        };
+
+        let mut output = String::new();
+
+        for y in (-quiet..size + quiet).step_by(2) {
+            for x in -quiet..size + quiet {
+                output.push(match (is_light(x, y), is_light(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+fn character_count_bits(version: usize) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+fn size_of(version: usize) -> usize {
+    version * 4 + 17
+}
+
+// the number of modules left for data and error correction after the function patterns
+fn raw_data_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+
+    modules
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version] * ERROR_CORRECTION_BLOCKS[version]
+}
+
+// the byte mode segment, terminated and padded to the version's capacity
+fn data_codewords_for(bytes: &[u8], version: usize) -> Vec<u8> {
+    let capacity = data_codewords(version) * 8;
+    let mut bits = Bits::default();
+
+    bits.push(0b0100, 4);
+    bits.push(bytes.len() as u32, character_count_bits(version));
+    for byte in bytes {
+        bits.push(u32::from(*byte), 8);
+    }
+
+    bits.push(0, 4.min(capacity - bits.len));
+    bits.push(0, (8 - bits.len % 8) % 8);
+
+    for pad in [0xec, 0x11].iter().cycle() {
+        if bits.len >= capacity {
+            break;
+        }
+        bits.push(*pad, 8);
+    }
+
+    bits.bytes
+}
+
+// split the data into blocks, add each block's error correction, and interleave them
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = ERROR_CORRECTION_BLOCKS[version];
+    let ecc_length = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_block_length = raw_codewords / blocks;
+    let divisor = reed_solomon_divisor(ecc_length);
+
+    let mut split = vec![];
+    let mut start = 0;
+
+    for block in 0..blocks {
+        let data_length = short_block_length - ecc_length + usize::from(block >= short_blocks);
+        let mut codewords = data[start..start + data_length].to_vec();
+        start += data_length;
+
+        let ecc = reed_solomon_remainder(&codewords, &divisor);
+        // a placeholder to line up with the long blocks, skipped when interleaving
+        if block < short_blocks {
+            codewords.push(0);
+        }
+        codewords.extend(ecc);
+        split.push(codewords);
+    }
+
+    let mut interleaved = Vec::with_capacity(raw_codewords);
+    for i in 0..split[0].len() {
+        for (block, codewords) in split.iter().enumerate() {
+            if i != short_block_length - ecc_length || block >= short_blocks {
+                interleaved.push(codewords[i]);
+            }
+        }
+    }
+
+    interleaved
+}
+
+// multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= u32::from((y >> i) & 1) * u32::from(x);
+    }
+
+    z as u8
+}
+
+// the generator polynomial of the given degree, highest coefficient first,
+// without its leading 1
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_multiply(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+
+    divisor
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0; divisor.len()];
+
+    for byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (coefficient, divisor) in remainder.iter_mut().zip(divisor) {
+            *coefficient ^= gf_multiply(*divisor, factor);
+        }
+    }
+
+    remainder
+}
+
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    // append the low `count` bits of `value`, most significant first
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().expect("a byte was just pushed") |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Grid {
+    version: usize,
+    size: usize,
+    // indexed [y][x]
+    modules: Vec<Vec<bool>>,
+    is_function: Vec<Vec<bool>>,
+}
+
+impl Grid {
+    fn new(version: usize) -> Self {
+        let size = size_of(version);
+
+        Self {
+            version,
+            size,
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+        }
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+
+        let positions = alignment_positions(self.version);
+        let last = positions.len().saturating_sub(1);
+        for (i, x) in positions.iter().enumerate() {
+            for (j, y) in positions.iter().enumerate() {
+                // the corners with finders
+                if (i, j) != (0, 0) && (i, j) != (0, last) && (i, j) != (last, 0) {
+                    self.draw_alignment(*x, *y);
+                }
+            }
+        }
+
+        // reserve the format areas, drawn for real once the mask is chosen
+        self.draw_format_bits(0);
+        self.draw_version();
+    }
+
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4isize..=4 {
+            for dx in -4isize..=4 {
+                let distance = dx.abs().max(dy.abs());
+                let (xx, yy) = (x as isize + dx, y as isize + dy);
+
+                if (0..self.size as isize).contains(&xx) && (0..self.size as isize).contains(&yy) {
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2isize..=2 {
+            for dx in -2isize..=2 {
+                let distance = dx.abs().max(dy.abs());
+                self.set_function(
+                    (x as isize + dx) as usize,
+                    (y as isize + dy) as usize,
+                    distance != 1,
+                );
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = LEVEL_L << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+
+        // around the top left finder
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // split between the other two finders
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+
+        let mut remainder = self.version as u32;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+        }
+        let bits = (self.version as u32) << 12 | remainder;
+
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 == 1;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    // fill the data area in the zigzag order, two columns at a time from the right
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+
+        loop {
+            // skip the vertical timing pattern
+            if right == 6 {
+                right = 5;
+            }
+
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+
+                    if !self.is_function[y][x] && i < codewords.len() * 8 {
+                        self.modules[y][x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+
+            if right < 3 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    // the standard's penalty score: long runs, 2x2 blocks, finder lookalikes, and imbalance
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let row = |y: usize| self.modules[y].clone();
+        let column = |x: usize| (0..size).map(|y| self.modules[y][x]).collect::<Vec<bool>>();
+        let lines = (0..size).map(row).chain((0..size).map(column));
+
+        let mut penalty = 0;
+
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                } else {
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+            }
+
+            const FINDER: [bool; 11] = [
+                true, false, true, true, true, false, true, false, false, false, false,
+            ];
+            for window in line.windows(11) {
+                if window.iter().eq(FINDER.iter()) || window.iter().rev().eq(FINDER.iter()) {
+                    penalty += 40;
+                }
+            }
+        }
+
+        for y in 1..size {
+            for x in 1..size {
+                let color = self.modules[y][x];
+                if self.modules[y - 1][x] == color
+                    && self.modules[y][x - 1] == color
+                    && self.modules[y - 1][x - 1] == color
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let dark = self.modules.iter().flatten().filter(|dark| **dark).count();
+        let total = size * size;
+        // 10 points for every 5% the dark share strays from half
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += deviation.div_ceil(total).saturating_sub(1) * 10;
+
+        penalty
+    }
+}
+
+// the centers of the alignment patterns along each axis
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let mut positions = vec![6];
+    let mut position = size_of(version) - 7;
+
+    for _ in 0..count - 1 {
+        positions.insert(1, position);
+        position -= step;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_error_correction() {
+        // "HELLO WORLD" at version 1-M, from the worked example at thonky.com
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+
+        assert_eq!(
+            reed_solomon_remainder(&data, &reed_solomon_divisor(10)),
+            vec![196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    #[test]
+    fn it_knows_the_capacity_of_each_version() {
+        let capacities = [1, 2, 5, 10, 40]
+            .iter()
+            .map(|version| data_codewords(*version))
+            .collect::<Vec<usize>>();
+
+        assert_eq!(capacities, vec![19, 34, 108, 274, 2956]);
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(alignment_positions(32), vec![6, 34, 60, 86, 112, 138]);
+    }
+
+    #[test]
+    fn it_picks_the_smallest_version() {
+        assert_eq!(QrCode::encode("hello").unwrap().size, 21);
+        assert_eq!(QrCode::encode(&"a".repeat(17)).unwrap().size, 21);
+        assert_eq!(QrCode::encode(&"a".repeat(18)).unwrap().size, 25);
+        assert_eq!(QrCode::encode(&"a".repeat(280)).unwrap().size, 61);
+        assert!(QrCode::encode(&"a".repeat(3000)).is_none());
+    }
+
+    #[test]
+    fn it_draws_the_finders() {
+        let code = QrCode::encode("hello").unwrap();
+
+        for (x, y) in [(0, 0), (14, 0), (0, 14)].iter() {
+            assert!(code.is_dark(*x, *y));
+            assert!(code.is_dark(x + 6, y + 6));
+            assert!(!code.is_dark(x + 1, y + 1));
+            assert!(code.is_dark(x + 3, y + 3));
+        }
+        // the dark module
+        assert!(code.is_dark(8, 13));
+    }
+}
@@ -0,0 +1,85 @@
+// a bug report written when ts panics, so users have something to attach to an issue
+// that says more than "it crashed".
+//
+// the report has the options and the size and hash of the input, but not the input itself.
+// only the binary installs the hook, so programs that embed `run` keep their own.
+
+use std::backtrace::Backtrace;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static OPTIONS: Mutex<Option<String>> = Mutex::new(None);
+static INPUT: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn path() -> PathBuf {
+    std::env::temp_dir().join("tweet_split-report.txt")
+}
+
+/// Write a report on any panic from now on, describing the options and input recorded.
+pub fn install() {
+    panic::set_hook(Box::new(|info| {
+        let recorded = |value: &Mutex<Option<String>>, otherwise: &str| {
+            value
+                .lock()
+                .ok()
+                .and_then(|value| value.clone())
+                .unwrap_or_else(|| otherwise.to_string())
+        };
+        let options = recorded(&OPTIONS, "not parsed yet");
+        let input = recorded(&INPUT, "not read yet");
+
+        let report = format!(
+            "tweet_split {}\n\n{}\n\noptions: {}\n\ninput: {}\n\nbacktrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            info,
+            options,
+            input,
+            Backtrace::force_capture()
+        );
+
+        match fs::write(path(), &report) {
+            Ok(()) => eprintln!(
+                "ts crashed, sorry! A bug report is in {}, and `ts --report` prints it.\n\
+                 Please attach it to an issue. It has your options and the size of your input, \
+                 but not the input itself.",
+                path().display()
+            ),
+            Err(_) => eprintln!(
+                "ts crashed, sorry! Please attach this to an issue:\n\n{}",
+                report
+            ),
+        }
+    }));
+}
+
+/// Describe the run with `options` in any later report.
+pub fn record_options(options: String) {
+    if let Ok(mut recorded) = OPTIONS.lock() {
+        *recorded = Some(options);
+    }
+}
+
+/// Describe `input` in any later report.
+pub fn record_input(input: &str) {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+
+    if let Ok(mut recorded) = INPUT.lock() {
+        *recorded = Some(format!(
+            "{} bytes, hash {:016x}",
+            input.len(),
+            hasher.finish()
+        ));
+    }
+}
+
+/// The report from the last crash.
+pub fn last() -> Result<String, Box<dyn Error>> {
+    fs::read_to_string(path())
+        .map_err(|error| format!("no bug report at {}: {}", path().display(), error).into())
+}
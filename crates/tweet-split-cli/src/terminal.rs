@@ -0,0 +1,22 @@
+// the size of the terminal, for re-wrapping human output to it
+
+/// The width of the terminal on stdout, if it is one.
+#[cfg(unix)]
+pub fn terminal_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: TIOCGWINSZ only writes a winsize into the pointer it is given
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+
+    Some(usize::from(size.ws_col)).filter(|columns| result == 0 && *columns > 0)
+}
+
+#[cfg(not(unix))]
+pub fn terminal_width() -> Option<usize> {
+    None
+}
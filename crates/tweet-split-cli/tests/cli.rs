@@ -0,0 +1,570 @@
+// runs ts end to end, as the built binary and in-process, checking what it prints and how it exits
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn ts(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tweet_split"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+// a file in the temp directory, unique to this test process
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ts-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+
+    path
+}
+
+const TEXT: &str = "the quick brown fox jumps over the lazy dog";
+
+#[test]
+fn it_splits_stdin() {
+    let output = ts(&["--format", "plain", "-l", "20"], TEXT);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "the quick brown fox\njumps over the lazy\ndog\n"
+    );
+}
+
+#[test]
+fn it_splits_files_and_words() {
+    let path = temp_file("input.txt", TEXT);
+    let from_file = ts(
+        &[
+            "--format",
+            "plain",
+            "-l",
+            "20",
+            "-i",
+            path.to_str().unwrap(),
+        ],
+        "",
+    );
+    let from_words = ts(
+        &["--format", "plain", "-l", "20", "the", "quick", "brown"],
+        "",
+    );
+    // words after a list flag are still the text, not more of its values
+    let tagged = ts(
+        &[
+            "--format",
+            "plain",
+            "-l",
+            "60",
+            "--hashtags",
+            "rust,cli",
+            "here",
+            "is",
+            "my",
+            "text",
+        ],
+        "from stdin",
+    );
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(
+        stdout(&from_file),
+        "the quick brown fox\njumps over the lazy\ndog\n"
+    );
+    assert_eq!(stdout(&from_words), "the quick brown\n");
+    assert_eq!(stdout(&tagged), "here is my text #rust #cli\n");
+    assert_eq!(
+        stdout(&ts(
+            &[
+                "-l",
+                "12",
+                "--reply-mentions",
+                "bob",
+                "here",
+                "is",
+                "my",
+                "text"
+            ],
+            "from stdin"
+        )),
+        "here is my\ntext\n"
+    );
+}
+
+#[test]
+fn it_prints_thread_stats() {
+    let output = ts(&["--stats", "-l", "20"], TEXT);
+
+    assert_eq!(
+        stdout(&output),
+        "tweet  length  used\n    1      19   95%\n    2      19   95%\n    3       3   15%\n\
+         3 tweets, 41 long in all, of 20 each\nused: least 15%, mean 68%, most 95%\n"
+    );
+}
+
+#[test]
+fn it_refuses_urls_offline() {
+    let output = ts(&["--offline", "-i", "https://example.com/post.txt"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("--offline forbids network input"));
+}
+
+#[test]
+fn it_prints_every_format() {
+    let human = ts(&["--format", "human"], "hello");
+    let plain = ts(&["--format", "plain"], "hello\nworld");
+    let json = ts(&["--format", "json"], "hello");
+    let two = "hello \"to\"\nyou";
+    let jsonl = ts(&["--format", "jsonl", "-l", "8"], two);
+    let csv = ts(&["--format", "csv", "-l", "8"], two);
+    let yaml = ts(&["--format", "yaml", "-l", "8"], two);
+    // piped, as under test, auto prints plain
+    let auto = ts(&[], "hello\nworld");
+
+    assert_eq!(stdout(&human), "--- 1/1 (length 5) ---\nhello\n");
+    assert_eq!(stdout(&plain), "hello\nworld\n");
+    assert!(stdout(&json).starts_with(
+        r#"[{"index":1,"total":1,"text":"hello","length":5,"entities":[],"budget":{"#
+    ));
+    assert!(stdout(&json).ends_with("}}]\n"));
+    assert_eq!(stdout(&ts(&["--format", "json"], "   ")), "[]\n");
+    assert!(stdout(&jsonl).starts_with(r#"{"index":1,"total":2,"text":"hello","#));
+    assert_eq!(stdout(&jsonl).lines().count(), 2);
+    assert_eq!(
+        stdout(&csv),
+        "index,total,length,tweet\n1,2,5,\"hello\"\n2,2,8,\"\"\"to\"\"\nyou\"\n"
+    );
+    assert_eq!(
+        stdout(&yaml),
+        "- index: 1\n  total: 2\n  length: 5\n  tweet: \"hello\"\n\
+         - index: 2\n  total: 2\n  length: 8\n  tweet: \"\\\"to\\\"\\nyou\"\n"
+    );
+    assert_eq!(stdout(&auto), stdout(&plain));
+    assert_eq!(stdout(&ts(&["-0", "-l", "8"], two)), "hello\0\"to\"\nyou\0");
+}
+
+#[test]
+fn it_prints_tweets_as_they_are_split() {
+    let input = "fine then incomprehensibilities";
+    let streamed = ts(&["-l", "10"], input);
+    let counted = ts(&["-l", "10", "-n", "slash"], input);
+
+    // with nothing needing the total, tweets before the one that fails are already out
+    assert_eq!(streamed.status.code(), Some(1));
+    assert_eq!(stdout(&streamed), "fine then\n");
+    assert!(stderr(&streamed).starts_with("error[TS006]: "));
+    assert_eq!(counted.status.code(), Some(1));
+    assert!(stdout(&counted).is_empty());
+}
+
+#[test]
+fn it_reports_errors_with_codes() {
+    let output = ts(&["-l", "5"], "incomprehensible");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).starts_with("error[TS006]: "));
+    assert!(stderr(&output).contains("try `ts explain TS006`"));
+
+    let usage = ts(&["--no-such-flag"], "");
+    assert_eq!(usage.status.code(), Some(1));
+    assert!(stderr(&usage).contains("--no-such-flag"));
+}
+
+#[test]
+fn it_explains_error_codes() {
+    let output = ts(&["explain", "ts006"], "");
+
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("TS006: a word is longer than a tweet"));
+    assert!(!ts(&["explain", "TS999"], "").status.success());
+}
+
+#[test]
+fn it_announces_a_release_from_the_changelog() {
+    let changelog = temp_file(
+        "CHANGELOG.md",
+        "# 1.1.0\n- thread numbering\n- templates\n\n# 1.0.0\n- the first release\n",
+    );
+    let announce = |heading: &str| {
+        ts(
+            &[
+                "announce",
+                "--changelog",
+                changelog.to_str().unwrap(),
+                "--changelog-version",
+                "1.1.0",
+                "--changelog-heading",
+                heading,
+                "-l",
+                "20",
+            ],
+            "",
+        )
+    };
+    let announced = announce("#");
+    let missing = announce("##");
+    std::fs::remove_file(&changelog).unwrap();
+
+    assert_eq!(stdout(&announced), "Thread numbering.\nTemplates.\n");
+    assert!(stderr(&missing).contains("no section for 1.1.0"));
+    assert!(stderr(&ts(&["announce", TEXT], "")).contains("needs --from-git or --changelog"));
+}
+
+#[test]
+fn it_starts_from_a_profile() {
+    let config = temp_file(
+        "config.toml",
+        "[profile.rant]\nmax-tweet-length = 20\nformat = \"plain\"\nhashtags = [\"rust\"]\n",
+    );
+    let config = config.to_str().unwrap();
+
+    let profile = ts(
+        &[
+            "--config",
+            config,
+            "--profile",
+            "rant",
+            "the quick brown fox",
+        ],
+        "",
+    );
+    let overridden = ts(
+        &[
+            "--config",
+            config,
+            "--profile",
+            "rant",
+            "-l",
+            "30",
+            "the quick brown fox",
+        ],
+        "",
+    );
+    let missing = ts(&["--config", config, "--profile", "calm", "hi"], "");
+    std::fs::remove_file(config).unwrap();
+
+    assert_eq!(stdout(&profile), "the quick brown\nfox #rust\n");
+    assert_eq!(stdout(&overridden), "the quick brown fox #rust\n");
+    assert!(stderr(&missing).contains("no profile `calm`, only: rant"));
+}
+
+#[test]
+fn it_shows_resolved_profiles() {
+    let config = temp_file(
+        "inherits.toml",
+        "[profile.base]\nmax-tweet-length = 20\nformat = \"plain\"\n\n\
+         [profile.long]\ninherits = \"base\"\nmax-tweet-length = 40\n",
+    );
+    let config = config.to_str().unwrap();
+
+    let shown = ts(
+        &[
+            "config",
+            "show",
+            "--resolved",
+            "--profile",
+            "long",
+            "--config",
+            config,
+        ],
+        "",
+    );
+    let split = ts(
+        &[
+            "--config",
+            config,
+            "--profile",
+            "long",
+            "the quick brown fox",
+        ],
+        "",
+    );
+    std::fs::remove_file(config).unwrap();
+
+    assert_eq!(
+        stdout(&shown),
+        format!(
+            "# {}\n\n[profile.long]\nmax-tweet-length = 40\nformat = \"plain\"\n",
+            config
+        )
+    );
+    assert_eq!(stdout(&split), "the quick brown fox\n");
+}
+
+#[test]
+fn it_sets_and_gets_profile_options() {
+    let config = temp_file("set.toml", "# mine\n");
+    let config = config.to_str().unwrap();
+    let config_command = |args: &[&str]| {
+        let mut args = args.to_vec();
+        args.extend(["--config", config]);
+        ts(&args, "")
+    };
+
+    let set = config_command(&["config", "set", "profile.rant.numbering", "slash"]);
+    let invalid = config_command(&["config", "set", "profile.rant.numbering", "fancy"]);
+    let got = config_command(&["config", "get", "profile.rant.numbering"]);
+    let written = std::fs::read_to_string(config).unwrap();
+    std::fs::remove_file(config).unwrap();
+
+    assert!(set.status.success(), "{}", stderr(&set));
+    assert!(stderr(&invalid).contains("Unknown numbering style `fancy`"));
+    assert_eq!(stdout(&got), "slash\n");
+    assert_eq!(written, "# mine\n\n[profile.rant]\nnumbering = \"slash\"\n");
+}
+
+#[test]
+fn it_diagnoses_broken_configs() {
+    let config = temp_file("doctor.toml", "[profile.bad]\nnumbering = \"fancy\"\n");
+    let output = ts(&["doctor", "--config", config.to_str().unwrap()], "");
+    std::fs::remove_file(config).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("error   config: in profile `bad`"));
+    assert!(stdout(&output).contains("ok      terminal: stdout is not a terminal"));
+}
+
+#[test]
+fn it_passes_its_self_test() {
+    let output = ts(&["--self-test"], "");
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("ok"));
+}
+
+#[test]
+fn it_runs_in_process() {
+    let mut stdin = TEXT.as_bytes();
+    let mut stdout = vec![];
+    let io = tweet_split_cli::Io {
+        stdin: &mut stdin,
+        stdout: &mut stdout,
+        interactive: false,
+    };
+
+    tweet_split_cli::run(&["ts", "-l", "20"], io).unwrap();
+
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "the quick brown fox\njumps over the lazy\ndog\n"
+    );
+}
+
+#[test]
+fn it_reopens_code_fences_for_discord() {
+    let output = ts(
+        &[
+            "--format",
+            "plain",
+            "--code-fences",
+            "--escape",
+            "shell",
+            "--strategy",
+            "lines",
+            "-l",
+            "33",
+        ],
+        "look:\n```rust\nlet a = 1;\nlet b = 2;\n```",
+    );
+
+    assert_eq!(
+        stdout(&output),
+        "look:\\n```rust\\nlet a = 1;\\n```\n```rust\\nlet b = 2;\\n```\n"
+    );
+}
+
+#[test]
+fn it_counts_checks_and_joins_as_subcommands() {
+    let count = ts(
+        &["count", "--length", "--count", "utf16", "héllo wörld"],
+        "",
+    );
+    let fits = ts(&["check", "-l", "20", TEXT], "");
+    let split = ts(
+        &[
+            "split",
+            "--format",
+            "plain",
+            "-l",
+            "24",
+            "--numbering",
+            "slash",
+        ],
+        TEXT,
+    );
+    let joined = ts(&["join", "--numbering", "slash"], &stdout(&split));
+
+    assert_eq!(stdout(&count), "11\n");
+    assert_eq!(stdout(&ts(&["--count-tweets", "-l", "20"], TEXT)), "3\n");
+    assert_eq!(stdout(&ts(&["count", "--count-tweets", TEXT], "")), "1\n");
+    assert_eq!(stdout(&ts(&["count", "-l", "20", TEXT], "")), "3\n");
+    assert!(stderr(&ts(&["split", "--length", TEXT], "")).contains("only for `ts count`"));
+    assert_eq!(fits.status.code(), Some(1));
+    assert!(stderr(&fits).contains("takes 3 tweets of 20"));
+    assert_eq!(stdout(&ts(&["check", "hi"], "")), "ok: 2 of 280\n");
+    assert_eq!(stdout(&joined), format!("{}\n", TEXT));
+
+    // each subcommand has its own usage, and a subcommand's name after an option is text
+    assert!(stdout(&ts(&["explain", "--help"], "")).contains("explain [code]"));
+    assert!(stdout(&ts(&["--help"], "")).contains("Print how many tweets the text takes"));
+    assert_eq!(
+        stdout(&ts(&["-l", "20", "count", "these"], "")),
+        "count these\n"
+    );
+}
+
+#[test]
+fn it_numbers_sections_but_ends_the_thread_once() {
+    let notes = "# One\nfirst part\n# Two\nsecond part\nbye";
+    let section = |flags: &[&str]| {
+        let mut args = vec!["-l", "40", "--numbering-scope", "section", "-n", "slash"];
+        args.extend_from_slice(flags);
+        stdout(&ts(&args, notes))
+    };
+
+    assert_eq!(
+        section(&["--end-marker"]),
+        "# One\nfirst part 1.1/1.1\n# Two\nsecond part\nbye 2.1/2.1 /end\n"
+    );
+    assert_eq!(
+        section(&["--sign-off-lines", "1"]),
+        "# One\nfirst part 1.1/1.1\n# Two\nsecond part 2.1/2.2\nbye 2.2/2.2\n"
+    );
+}
+
+#[test]
+fn it_escapes_and_separates_on_request() {
+    let two = "hello \"to\"\nyou";
+    let shell = ts(&["--escape", "shell", "-l", "8"], two);
+    let json = ts(&["--escape", "json", "-l", "8"], two);
+    let separated = ts(&["--separator", "\n--\n", "-l", "8"], two);
+
+    assert_eq!(stdout(&shell), "hello\n\\\"to\\\"\\nyou\n");
+    assert_eq!(stdout(&json), "\"hello\"\n\"\\\"to\\\"\\nyou\"\n");
+    assert_eq!(stdout(&separated), "hello\n--\n\"to\"\nyou\n--\n");
+
+    let joined = |args: &[&str], output: &Output| stdout(&ts(args, &stdout(output)));
+    assert_eq!(
+        joined(&["join", "--escape", "shell"], &shell),
+        "hello \"to\"\nyou\n"
+    );
+    assert_eq!(
+        joined(&["join", "--escape", "json"], &json),
+        "hello \"to\"\nyou\n"
+    );
+    assert_eq!(
+        joined(&["join", "--separator", "\n--\n"], &separated),
+        "hello \"to\"\nyou\n"
+    );
+}
+
+#[test]
+fn it_checks_accessibility() {
+    let text = "big news 🎉🎉🎉🎉 #throwbackthursday";
+    let warned = ts(&["--a11y", "warn", "--format", "plain", text], "");
+    let strict = ts(&["--a11y", "strict", text], "");
+
+    assert!(warned.status.success());
+    assert_eq!(stdout(&warned), format!("{}\n", text));
+    assert!(stderr(&warned).contains("warning: tweet 1 has a run of 4 emoji at byte 9"));
+    assert!(stderr(&warned).contains("`#throwbackthursday`"));
+    assert_eq!(strict.status.code(), Some(1));
+    assert!(stderr(&strict).contains("1 of 1 tweets read badly with a screen reader."));
+    assert!(ts(&["--a11y", "strict", "#RustLang"], "").status.success());
+}
+
+#[test]
+fn it_camel_cases_hashtags() {
+    let words = temp_file("words.txt", "rust\nLang\ncommunity\n");
+    let output = ts(
+        &[
+            "--hashtag-words",
+            words.to_str().unwrap(),
+            "--camel-hashtag",
+            "TIL",
+            "#til #rustlangcommunity #rust",
+        ],
+        "",
+    );
+    std::fs::remove_file(words).unwrap();
+
+    assert_eq!(stdout(&output), "#TIL #RustLangCommunity #rust\n");
+}
+
+#[test]
+fn it_writes_each_tweet_to_a_file() {
+    let dir = std::env::temp_dir().join(format!("ts-cli-{}-out", std::process::id()));
+    let output = ts(&["--out-dir", dir.to_str().unwrap(), "-l", "20"], TEXT);
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).is_empty());
+    assert_eq!(read("001.txt"), "the quick brown fox");
+    assert_eq!(read("003.txt"), "dog");
+    assert!(!dir.join("004.txt").exists());
+
+    // a shorter thread replaces a longer one, leaving files ts didn't write alone
+    for name in ["notes.txt", "2024.txt", "7.txt"] {
+        std::fs::write(dir.join(name), "keep").unwrap();
+    }
+    let shorter = ts(
+        &["--out-dir", dir.to_str().unwrap(), "-l", "20"],
+        "hello world",
+    );
+
+    assert!(shorter.status.success(), "{}", stderr(&shorter));
+    assert_eq!(read("001.txt"), "hello world");
+    assert!(!dir.join("002.txt").exists());
+    assert!(!dir.join("003.txt").exists());
+    assert_eq!(read("notes.txt"), "keep");
+    assert_eq!(read("2024.txt"), "keep");
+    assert_eq!(read("7.txt"), "keep");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn it_splits_several_files() {
+    let dir = std::env::temp_dir().join(format!("ts-cli-{}-files", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.md"), "the quick brown fox").unwrap();
+    std::fs::write(dir.join("b.md"), "jumps over the lazy dog").unwrap();
+    std::fs::write(dir.join("c.txt"), "not this one").unwrap();
+    let pattern = dir.join("*.md");
+    let output = ts(&["--files", "-l", "20", pattern.to_str().unwrap()], "");
+    let missing = ts(&["--files", dir.join("*.rs").to_str().unwrap()], "");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "==> {} <==\nthe quick brown fox\n\n==> {} <==\njumps over the lazy\ndog\n",
+            dir.join("a.md").display(),
+            dir.join("b.md").display()
+        )
+    );
+    assert!(stderr(&missing).contains("no files match"));
+}
@@ -0,0 +1,54 @@
+// how long the multi-document pipeline takes, parsing a JSON Lines batch and
+// splitting every draft, to compare builds of ts like ones with another allocator.
+// run with `cargo bench -p tweet-split-core --bench batch`
+
+use std::hint::black_box;
+use std::time::Instant;
+use tweet_split_core::batch;
+use tweet_split_core::numbering::{Numbering, NumberingPosition, NumberingStyle};
+use tweet_split_core::{split_thread, SplitOptions};
+
+const DRAFTS: usize = 20_000;
+
+const TEXT: &str = "The traitorous eight was a group of eight employees who left Shockley \
+    Semiconductor Laboratory in 1957 to found Fairchild Semiconductor. William Shockley had \
+    in 1956 recruited a group of young PhD graduates with the goal to develop and produce new \
+    semiconductor devices.";
+
+fn main() {
+    let source = (0..DRAFTS)
+        .map(|draft| {
+            format!(
+                "{{\"name\": \"draft {}\", \"text\": \"{} {}\"}}\n",
+                draft, draft, TEXT
+            )
+        })
+        .collect::<String>();
+
+    let mut options = SplitOptions::new(80);
+    options.numbering = Some(Numbering::new(
+        NumberingStyle::Slash,
+        NumberingPosition::Suffix,
+    ));
+
+    let start = Instant::now();
+    let drafts = batch::parse_jsonl(&source).unwrap();
+    let parsed = start.elapsed();
+
+    let mut tweets = 0;
+    for draft in &drafts {
+        tweets += black_box(split_thread(&draft.text, &options).unwrap())
+            .tweets
+            .len();
+    }
+    let split = start.elapsed() - parsed;
+
+    println!(
+        "{} drafts, {} tweets: parsed in {:?}, split in {:?} ({:.1} µs/draft)",
+        drafts.len(),
+        tweets,
+        parsed,
+        split,
+        split.as_secs_f64() * 1e6 / drafts.len() as f64
+    );
+}
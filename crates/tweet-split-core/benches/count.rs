@@ -0,0 +1,53 @@
+// how long each counter takes on a tweet's worth of text, the work a live
+// character counter does on every keystroke. run with `cargo bench -p tweet-split-core`
+
+use std::hint::black_box;
+use std::time::Instant;
+use tweet_split_core::count::{CountMode, Counter};
+
+const ITERATIONS: u32 = 100_000;
+
+const TEXTS: &[(&str, &str)] = &[
+    (
+        "ascii",
+        "The traitorous eight was a group of eight employees who left Shockley Semiconductor \
+         Laboratory in 1957 to found Fairchild Semiconductor. William Shockley had in 1956 \
+         recruited a group of young PhD graduates.",
+    ),
+    (
+        "mixed",
+        "ok 👍🏽 see https://example.com/2026/10/a-long-link about 日本語 and \
+         👩\u{200D}👩\u{200D}👧 with café, naïve résumé “quotes” — and …€",
+    ),
+];
+
+fn main() {
+    let modes = [
+        CountMode::Bytes,
+        CountMode::Utf16,
+        CountMode::Graphemes,
+        CountMode::TwitterWeighted,
+        CountMode::Mastodon,
+        CountMode::Gsm7,
+    ];
+
+    for (name, text) in TEXTS {
+        for count in modes.iter() {
+            // the first call compiles the link pattern
+            black_box(count.count(text));
+
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                black_box(count.count(black_box(text)));
+            }
+            let elapsed = start.elapsed();
+
+            println!(
+                "{:>6} {:<16} {:>8.0} ns/count",
+                name,
+                format!("{:?}", count),
+                elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+            );
+        }
+    }
+}
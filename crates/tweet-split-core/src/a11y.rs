@@ -0,0 +1,146 @@
+// things in a tweet that read badly with a screen reader: letters styled with
+// mathematical symbols, long runs of emoji, and hashtags without capitals between words
+
+use crate::count::{is_emoji, CountMode};
+use crate::entities::{self, EntityKind};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The most emoji in a row, spaces between them or not, before it's a run worth warning about.
+pub const MAX_EMOJI_RUN: usize = 3;
+
+/// The fewest letters in a hashtag of one case before it's probably several words run together.
+pub const MIN_CASELESS_HASHTAG: usize = 8;
+
+/// Something a screen reader reads out badly, or not at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// Letters written in Unicode's mathematical alphanumerics, like 𝗯𝗼𝗹𝗱,
+    /// which screen readers spell out symbol by symbol or skip
+    StyledLetters { count: usize },
+    /// More than `MAX_EMOJI_RUN` emoji in a row, each read out by its full name
+    EmojiRun {
+        length: usize,
+        /// The byte offset of the run's first emoji in the tweet
+        offset: usize,
+    },
+    /// A long hashtag all in one case, like `#throwbackthursday`,
+    /// which screen readers read as one word instead of `#ThrowbackThursday`'s two
+    CaselessHashtag { tag: String },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::StyledLetters { count } => write!(
+                f,
+                "{} letters styled with math symbols, which screen readers spell out or skip",
+                count
+            ),
+            Issue::EmojiRun { length, offset } => write!(
+                f,
+                "a run of {} emoji at byte {}, each read out by name",
+                length, offset
+            ),
+            Issue::CaselessHashtag { tag } => write!(
+                f,
+                "the hashtag `{}`, which reads as one word unless written in CamelCase",
+                tag
+            ),
+        }
+    }
+}
+
+/// Every accessibility issue in `tweet`: its styled letters, then its runs of emoji and
+/// its hashtags in order.
+pub fn check(tweet: &str) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    let styled = tweet.chars().filter(|c| is_styled(*c)).count();
+    if styled > 0 {
+        issues.push(Issue::StyledLetters { count: styled });
+    }
+
+    let mut run: Option<(usize, usize)> = None;
+    for (offset, grapheme) in tweet.grapheme_indices(true).chain(Some((tweet.len(), ""))) {
+        if is_emoji(grapheme) {
+            let (start, length) = run.unwrap_or((offset, 0));
+            run = Some((start, length + 1));
+        } else if grapheme.trim().is_empty() && !grapheme.is_empty() {
+            continue;
+        } else if let Some((offset, length)) = run.take() {
+            if length > MAX_EMOJI_RUN {
+                issues.push(Issue::EmojiRun { length, offset });
+            }
+        }
+    }
+
+    for entity in entities::extract(tweet, CountMode::Bytes) {
+        let tag = entity.text(tweet);
+        if entity.kind == EntityKind::Hashtag && is_caseless(&tag[1..]) {
+            issues.push(Issue::CaselessHashtag {
+                tag: tag.to_string(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Every accessibility issue in each tweet of a thread, with the tweet's 0-based index.
+pub fn check_thread(tweets: &[String]) -> Vec<(usize, Issue)> {
+    tweets
+        .iter()
+        .enumerate()
+        .flat_map(|(index, tweet)| check(tweet).into_iter().map(move |issue| (index, issue)))
+        .collect()
+}
+
+// a letter or digit from the Mathematical Alphanumeric Symbols block
+fn is_styled(c: char) -> bool {
+    matches!(c as u32, 0x1D400..=0x1D7FF)
+}
+
+// whether a hashtag's letters are long enough to be several words,
+// but all one case, so nothing shows where one word ends
+fn is_caseless(tag: &str) -> bool {
+    let letters = tag
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<Vec<char>>();
+
+    letters.len() >= MIN_CASELESS_HASHTAG
+        && (letters.iter().all(|c| !c.is_uppercase()) || letters.iter().all(|c| !c.is_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_styled_letters_emoji_runs_and_caseless_hashtags() {
+        assert_eq!(
+            check("𝗯𝗼𝗹𝗱 news 🔥🔥 🔥🔥 #throwbackthursday #ThrowbackThursday #rust"),
+            vec![
+                Issue::StyledLetters { count: 4 },
+                Issue::EmojiRun {
+                    length: 4,
+                    offset: 22
+                },
+                Issue::CaselessHashtag {
+                    tag: "#throwbackthursday".to_string()
+                },
+            ]
+        );
+        assert_eq!(check("fine 🎉🎉🎉 #RustLang #TIL"), vec![]);
+        assert_eq!(
+            check_thread(&["ok".to_string(), "#ABSOLUTELYNOT 🙂".to_string()]),
+            vec![(
+                1,
+                Issue::CaselessHashtag {
+                    tag: "#ABSOLUTELYNOT".to_string()
+                }
+            )]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+// reading many drafts at once from JSON Lines or CSV
+//
+// every draft has a `text`, and any other fields are kept as per-draft settings:
+//
+// {"name": "launch", "text": "We shipped!", "max_length": 280}
+//
+// name,text,max_length
+// launch,We shipped!,280
+
+use crate::json::Json;
+use crate::TweetSplitError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Draft {
+    pub text: String,
+    /// Every field other than `text`, in file order,
+    /// with non-string JSON values written as JSON
+    pub fields: Vec<(String, String)>,
+}
+
+impl Draft {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse one JSON object per non-blank line.
+pub fn parse_jsonl(source: &str) -> Result<Vec<Draft>, TweetSplitError> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_number, line)| {
+            let at_line = |error: TweetSplitError| TweetSplitError::InvalidInput {
+                details: format!("Line {}: {}", line_number + 1, error),
+            };
+
+            let members = match Json::parse(line).map_err(at_line)? {
+                Json::Object(members) => members,
+                _ => return Err(at_line(missing_text())),
+            };
+
+            let fields = members
+                .into_iter()
+                .map(|(name, value)| match value {
+                    Json::String(string) => (name, string),
+                    other => (name, other.to_string()),
+                })
+                .collect();
+
+            draft(fields).map_err(at_line)
+        })
+        .collect()
+}
+
+/// Parse RFC 4180 CSV whose first record names the fields.
+pub fn parse_csv(source: &str) -> Result<Vec<Draft>, TweetSplitError> {
+    let mut records = csv_records(source)?.into_iter();
+    let header = records.next().unwrap_or_default();
+
+    records
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|field| !field.is_empty()))
+        .map(|(record_number, record)| {
+            let fields = header.iter().cloned().zip(record).collect();
+
+            draft(fields).map_err(|error| TweetSplitError::InvalidInput {
+                details: format!("Record {}: {}", record_number + 1, error),
+            })
+        })
+        .collect()
+}
+
+fn draft(mut fields: Vec<(String, String)>) -> Result<Draft, TweetSplitError> {
+    let text_field = fields
+        .iter()
+        .position(|(name, _)| name == "text")
+        .ok_or_else(missing_text)?;
+
+    let (_, text) = fields.remove(text_field);
+
+    Ok(Draft { text, fields })
+}
+
+fn missing_text() -> TweetSplitError {
+    TweetSplitError::InvalidInput {
+        details: "every draft needs a `text` field".to_string(),
+    }
+}
+
+fn csv_records(source: &str) -> Result<Vec<Vec<String>>, TweetSplitError> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => (),
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(TweetSplitError::InvalidInput {
+            details: "CSV ends inside a quoted field".to_string(),
+        });
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_jsonl_drafts() {
+        let source = r##"{"name": "launch", "text": "We shipped!", "max_length": 280}
+
+{"text": "second", "hashtags": ["#a", "#b"]}
+"##;
+
+        let drafts = parse_jsonl(source).unwrap();
+
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].text, "We shipped!");
+        assert_eq!(drafts[0].field("name"), Some("launch"));
+        assert_eq!(drafts[0].field("max_length"), Some("280"));
+        assert_eq!(drafts[1].field("hashtags"), Some(r##"["#a","#b"]"##));
+    }
+
+    #[test]
+    fn it_parses_csv_drafts() {
+        let source = "name,text\r\nlaunch,\"We shipped, \"\"finally\"\"\nhooray\"\n\n,second\n";
+
+        let drafts = parse_csv(source).unwrap();
+
+        assert_eq!(
+            drafts,
+            vec![
+                Draft {
+                    text: "We shipped, \"finally\"\nhooray".to_string(),
+                    fields: vec![("name".to_string(), "launch".to_string())],
+                },
+                Draft {
+                    text: "second".to_string(),
+                    fields: vec![("name".to_string(), String::new())],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_requires_text() {
+        assert!(parse_jsonl("{\"name\": \"no text\"}").is_err());
+        assert!(parse_jsonl("[1, 2]").is_err());
+        assert!(parse_csv("name\nx\n").is_err());
+        assert!(parse_csv("text\n\"unterminated\n").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+// extracting release notes from a keep-a-changelog style CHANGELOG
+//
+// ## [1.4.0] - 2020-06-01
+// ### Added
+// - thread numbering
+//
+// ## [1.3.0] - 2020-05-01
+// ...
+
+use std::str::FromStr;
+
+use crate::TweetSplitError;
+
+/// What to do with the bullet points of a changelog section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bullets {
+    /// Turn each bullet into a sentence and each subheading into a lead-in,
+    /// so the section reads as prose
+    Sentences,
+    /// Leave the section as a list, one bullet per line
+    Keep,
+}
+
+impl FromStr for Bullets {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sentences" => Ok(Bullets::Sentences),
+            "keep" => Ok(Bullets::Keep),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!("Unknown bullet style `{}`, expected sentences or keep.", s),
+            }),
+        }
+    }
+}
+
+/// The body of the section for `version`, without its heading.
+///
+/// Versions are matched with or without brackets and a leading `v`,
+/// so `1.4.0` finds `## [1.4.0] - 2020-06-01` and `## v1.4.0`.
+pub fn extract_section<'a>(changelog: &'a str, version: &str) -> Option<&'a str> {
+    extract_section_at(changelog, version, 2)
+}
+
+/// Like `extract_section`, for a changelog whose versions are headings of `level`,
+/// like 1 for `# 1.4.0`.
+pub fn extract_section_at<'a>(changelog: &'a str, version: &str, level: usize) -> Option<&'a str> {
+    let version = version.trim_start_matches('v');
+    let mut offset = 0;
+    let mut start = None;
+
+    for line in changelog.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        if heading_level(line) != Some(level) {
+            continue;
+        }
+
+        match start {
+            None if heading_version(line) == Some(version) => start = Some(offset),
+            Some(start) => return Some(changelog[start..line_start].trim()),
+            None => (),
+        }
+    }
+
+    start.map(|start| changelog[start..].trim())
+}
+
+/// Render a section body as text to split.
+pub fn render_section(section: &str, bullets: Bullets) -> String {
+    match bullets {
+        Bullets::Keep => section.to_string(),
+        Bullets::Sentences => {
+            let mut sentences = vec![];
+
+            for line in section
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+            {
+                if heading_level(line).is_some() {
+                    sentences.push(format!("{}:", line.trim_start_matches('#').trim()));
+                } else if let Some(item) = bullet_text(line) {
+                    sentences.push(sentence(item));
+                } else {
+                    sentences.push(line.to_string());
+                }
+            }
+
+            sentences.join(" ")
+        }
+    }
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+
+    if level > 0 && (rest.starts_with(' ') || rest.trim().is_empty()) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+// `1.4.0` from `## [1.4.0] - 2020-06-01` or `## v1.4.0`
+fn heading_version(line: &str) -> Option<&str> {
+    let title = line.trim_start_matches('#').trim();
+    let title = title.strip_prefix('[').unwrap_or(title);
+    let end = title
+        .find(|c: char| c == ']' || c.is_whitespace())
+        .unwrap_or(title.len());
+
+    Some(title[..end].trim_start_matches('v')).filter(|version| !version.is_empty())
+}
+
+fn bullet_text(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .map(str::trim)
+}
+
+// capitalize the first letter and end with punctuation
+fn sentence(item: &str) -> String {
+    let mut chars = item.chars();
+    let mut sentence = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+    };
+
+    if !sentence.ends_with(['.', '!', '?']) {
+        sentence.push('.');
+    }
+
+    sentence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog
+
+## [Unreleased]
+- something in progress
+
+## [1.4.0] - 2020-06-01
+### Added
+- thread numbering
+- templates for prefixes and suffixes!
+
+### Fixed
+* a crash on empty input
+
+## [1.3.0] - 2020-05-01
+- older things
+";
+
+    #[test]
+    fn it_extracts_a_section() {
+        let section = extract_section(CHANGELOG, "v1.4.0").unwrap();
+
+        assert!(section.starts_with("### Added"));
+        assert!(section.ends_with("* a crash on empty input"));
+        assert_eq!(extract_section(CHANGELOG, "1.3.0"), Some("- older things"));
+        assert_eq!(
+            extract_section(CHANGELOG, "Unreleased"),
+            Some("- something in progress")
+        );
+        assert_eq!(extract_section(CHANGELOG, "9.9.9"), None);
+        assert_eq!(
+            extract_section_at("# 2.0.0\n## Added\n- more\n# 1.0.0\n", "2.0.0", 1),
+            Some("## Added\n- more")
+        );
+    }
+
+    #[test]
+    fn it_turns_bullets_into_sentences() {
+        let section = extract_section(CHANGELOG, "1.4.0").unwrap();
+
+        assert_eq!(
+            render_section(section, Bullets::Sentences),
+            "Added: Thread numbering. Templates for prefixes and suffixes! Fixed: A crash on empty input."
+        );
+    }
+
+    #[test]
+    fn it_keeps_bullets() {
+        let section = extract_section(CHANGELOG, "1.3.0").unwrap();
+
+        assert_eq!(render_section(section, Bullets::Keep), "- older things");
+    }
+}
@@ -0,0 +1,245 @@
+// known lengths of tricky texts on each platform, for checking counters against
+
+use crate::count::Counter;
+use crate::platform::Platform;
+use std::fmt;
+
+/// A text and its length on every platform.
+///
+/// Texts are already in NFC, so counters that don't normalize agree with the ones that do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vector {
+    pub description: &'static str,
+    pub text: &'static str,
+    pub twitter: usize,
+    pub bluesky: usize,
+}
+
+impl Vector {
+    pub fn expected(&self, platform: Platform) -> usize {
+        match platform {
+            Platform::Twitter => self.twitter,
+            Platform::Bluesky => self.bluesky,
+        }
+    }
+}
+
+pub const VECTORS: &[Vector] = &[
+    Vector {
+        description: "empty",
+        text: "",
+        twitter: 0,
+        bluesky: 0,
+    },
+    Vector {
+        description: "ascii",
+        text: "hello, world",
+        twitter: 12,
+        bluesky: 12,
+    },
+    Vector {
+        description: "ascii whitespace",
+        text: "tab\tand\nnewline",
+        twitter: 15,
+        bluesky: 15,
+    },
+    Vector {
+        description: "precomposed accent",
+        text: "café",
+        twitter: 4,
+        bluesky: 4,
+    },
+    Vector {
+        description: "combining accent on a letter with no precomposed form",
+        text: "q\u{0301}",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "greek",
+        text: "Ελληνικά",
+        twitter: 8,
+        bluesky: 8,
+    },
+    Vector {
+        description: "cyrillic",
+        text: "Привет",
+        twitter: 6,
+        bluesky: 6,
+    },
+    Vector {
+        description: "thai with combining vowels",
+        text: "สวัสดี",
+        twitter: 6,
+        bluesky: 4,
+    },
+    Vector {
+        description: "japanese",
+        text: "日本語",
+        twitter: 6,
+        bluesky: 3,
+    },
+    Vector {
+        description: "hangul syllables",
+        text: "한국어",
+        twitter: 6,
+        bluesky: 3,
+    },
+    Vector {
+        description: "conjoining hangul jamo",
+        text: "\u{1100}\u{1161}\u{11A8}",
+        twitter: 6,
+        bluesky: 1,
+    },
+    Vector {
+        description: "fullwidth latin",
+        text: "ａｂｃ",
+        twitter: 6,
+        bluesky: 3,
+    },
+    Vector {
+        description: "mixed latin and cjk",
+        text: "Hi 日本",
+        twitter: 7,
+        bluesky: 5,
+    },
+    Vector {
+        description: "general punctuation counted as narrow",
+        text: "“quoted” — ‘marks’",
+        twitter: 18,
+        bluesky: 18,
+    },
+    Vector {
+        description: "ellipsis and euro sign, counted as wide",
+        text: "…€",
+        twitter: 4,
+        bluesky: 2,
+    },
+    Vector {
+        description: "emoji",
+        text: "😀",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "emoji with a skin tone",
+        text: "👍🏽",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "zero width joiner family",
+        text: "👩\u{200D}👩\u{200D}👧\u{200D}👦",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "flag",
+        text: "🇯🇵",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "keycap",
+        text: "1\u{FE0F}\u{20E3}",
+        twitter: 2,
+        bluesky: 1,
+    },
+    Vector {
+        description: "text around emoji",
+        text: "ok 👍🏽 thanks 🙏",
+        twitter: 15,
+        bluesky: 13,
+    },
+];
+
+/// A vector that a counter measured differently than the platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub vector: &'static Vector,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:?}): expected {}, counted {}",
+            self.vector.description, self.vector.text, self.expected, self.actual
+        )
+    }
+}
+
+/// Measure every vector with `counter`, returning the ones it gets wrong for `platform`.
+pub fn check<C>(counter: &C, platform: Platform) -> Result<(), Vec<Mismatch>>
+where
+    C: Counter + ?Sized,
+{
+    let mismatches = VECTORS
+        .iter()
+        .filter_map(|vector| {
+            let expected = vector.expected(platform);
+            let actual = counter.count(vector.text);
+
+            if actual == expected {
+                None
+            } else {
+                Some(Mismatch {
+                    vector,
+                    expected,
+                    actual,
+                })
+            }
+        })
+        .collect::<Vec<Mismatch>>();
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(platform: Platform) -> impl Fn(&str) -> usize {
+        move |text| {
+            VECTORS
+                .iter()
+                .find(|vector| vector.text == text)
+                .map(|vector| vector.expected(platform))
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn it_passes_counters_that_agree() {
+        assert_eq!(check(&lookup(Platform::Twitter), Platform::Twitter), Ok(()));
+        assert_eq!(check(&lookup(Platform::Bluesky), Platform::Bluesky), Ok(()));
+    }
+
+    #[test]
+    fn it_reports_what_a_counter_gets_wrong() {
+        let mismatches = check(&|text: &str| text.chars().count(), Platform::Bluesky).unwrap_err();
+
+        let family = mismatches
+            .iter()
+            .find(|mismatch| mismatch.vector.description == "zero width joiner family")
+            .unwrap();
+        assert_eq!((family.expected, family.actual), (1, 7));
+        assert!(mismatches
+            .iter()
+            .all(|mismatch| mismatch.vector.text.chars().count() != mismatch.expected));
+    }
+
+    #[test]
+    fn it_counts_ascii_the_same_everywhere() {
+        for vector in VECTORS.iter().filter(|vector| vector.text.is_ascii()) {
+            assert_eq!(vector.twitter, vector.text.len(), "{}", vector.description);
+            assert_eq!(vector.bluesky, vector.text.len(), "{}", vector.description);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// lookalike letters and invisible characters in the source text,
+// usually pasted in from a PDF or a web page, that waste room and can look like spam
+
+use lazy_static::*;
+use regex::Regex;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Script> {
+        if !c.is_alphabetic() {
+            return None;
+        }
+
+        match c as u32 {
+            0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F | 0x1E00..=0x1EFF => Some(Script::Latin),
+            0x370..=0x3FF | 0x1F00..=0x1FFF => Some(Script::Greek),
+            0x400..=0x52F => Some(Script::Cyrillic),
+            _ => None,
+        }
+    }
+}
+
+/// Something in the text that is probably not what it looks like.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Suspicion {
+    /// A word with letters from more than one script, some of them lookalikes,
+    /// like "pаypal" with a Cyrillic "а"
+    MixedScripts {
+        word: String,
+        /// The byte offset of the word in the text
+        offset: usize,
+        /// The scripts of the word's letters, in the order they first appear
+        scripts: Vec<Script>,
+    },
+    /// A character that takes up room but shows nothing, like a zero-width space
+    Invisible {
+        character: char,
+        name: &'static str,
+        /// The byte offset of the character in the text
+        offset: usize,
+    },
+}
+
+impl Suspicion {
+    pub fn offset(&self) -> usize {
+        match self {
+            Suspicion::MixedScripts { offset, .. } | Suspicion::Invisible { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for Suspicion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Suspicion::MixedScripts { word, scripts, .. } => {
+                let scripts = scripts
+                    .iter()
+                    .map(|script| format!("{:?}", script))
+                    .collect::<Vec<String>>();
+                write!(f, "`{}` mixes {} letters", word, scripts.join(" and "))
+            }
+            Suspicion::Invisible {
+                character, name, ..
+            } => write!(
+                f,
+                "the invisible character U+{:04X} ({})",
+                *character as u32, name
+            ),
+        }
+    }
+}
+
+/// Every lookalike word and invisible character in `text`, in order.
+///
+/// Zero-width joiners and non-joiners are left alone, since emoji and some scripts need them.
+pub fn check(text: &str) -> Vec<Suspicion> {
+    let mut suspicions = vec![];
+
+    for word in WORD.find_iter(text) {
+        let mut scripts = vec![];
+        let mut lookalike = false;
+
+        for c in word.as_str().chars() {
+            if let Some(script) = Script::of(c) {
+                if !scripts.contains(&script) {
+                    scripts.push(script);
+                }
+                lookalike |= LOOKALIKES.contains(c);
+            }
+        }
+
+        if scripts.len() > 1 && lookalike {
+            suspicions.push(Suspicion::MixedScripts {
+                word: word.as_str().to_string(),
+                offset: word.start(),
+                scripts,
+            });
+        }
+
+        for (offset, character) in word.as_str().char_indices() {
+            if let Some((_, name)) = INVISIBLE.iter().find(|(c, _)| *c == character) {
+                suspicions.push(Suspicion::Invisible {
+                    character,
+                    name,
+                    offset: word.start() + offset,
+                });
+            }
+        }
+    }
+
+    suspicions
+}
+
+// Greek and Cyrillic letters that look like Latin ones
+const LOOKALIKES: &str = "АВЕЅІЈКМНОРСТХаеіјорсуѕхԁԛԝΑΒΕΖΗΙΚΜΝΟΡΤΥΧικνορυχ";
+
+const INVISIBLE: &[(char, &str)] = &[
+    ('\u{00AD}', "soft hyphen"),
+    ('\u{034F}', "combining grapheme joiner"),
+    ('\u{115F}', "hangul choseong filler"),
+    ('\u{1160}', "hangul jungseong filler"),
+    ('\u{180E}', "mongolian vowel separator"),
+    ('\u{200B}', "zero width space"),
+    ('\u{2060}', "word joiner"),
+    ('\u{2061}', "function application"),
+    ('\u{2062}', "invisible times"),
+    ('\u{2063}', "invisible separator"),
+    ('\u{2064}', "invisible plus"),
+    ('\u{3164}', "hangul filler"),
+    ('\u{FEFF}', "zero width no-break space"),
+];
+
+lazy_static! {
+    static ref WORD: Regex = Regex::new(r"\S+").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_words_with_lookalike_letters() {
+        let text = "log in to p\u{0430}ypal and Москвa";
+
+        assert_eq!(
+            check(text),
+            vec![
+                Suspicion::MixedScripts {
+                    word: "p\u{0430}ypal".to_string(),
+                    offset: 10,
+                    scripts: vec![Script::Latin, Script::Cyrillic],
+                },
+                Suspicion::MixedScripts {
+                    word: "Москвa".to_string(),
+                    offset: 22,
+                    scripts: vec![Script::Cyrillic, Script::Latin],
+                },
+            ]
+        );
+        assert_eq!(
+            check(text)[0].to_string(),
+            "`p\u{0430}ypal` mixes Latin and Cyrillic letters"
+        );
+    }
+
+    #[test]
+    fn it_leaves_honest_mixed_words_alone() {
+        assert_eq!(check("λ-calculus, café, Ελληνικά, Привет 👩‍👩‍👧"), vec![]);
+    }
+
+    #[test]
+    fn it_finds_invisible_characters() {
+        let suspicions = check("hy\u{00AD}phen\u{200B}ated");
+
+        assert_eq!(
+            suspicions.iter().map(Suspicion::offset).collect::<Vec<_>>(),
+            vec![2, 8]
+        );
+        assert_eq!(
+            suspicions[1].to_string(),
+            "the invisible character U+200B (zero width space)"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// measuring the length of text the way a platform does
+
+use crate::entities;
+use crate::sms;
+use crate::TweetSplitError;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A way of measuring the length of text against a tweet length limit.
+pub trait Counter {
+    fn count(&self, text: &str) -> usize;
+}
+
+impl<F> Counter for F
+where
+    F: Fn(&str) -> usize,
+{
+    #[inline]
+    fn count(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// The unit tweet lengths are measured in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CountMode {
+    /// UTF-8 bytes, which over-counts anything outside ASCII
+    #[default]
+    Bytes,
+    /// UTF-16 code units, which is what JavaScript's `length`
+    /// and so most web text fields count
+    Utf16,
+    /// Extended grapheme clusters, so that 👩‍👩‍👧‍👦 or an accented letter counts 1,
+    /// as on Bluesky
+    Graphemes,
+    /// twitter-text's weighted length, which Twitter's 280 limit is measured in:
+    /// code points in Latin, Greek, Cyrillic, and most other alphabets, and in
+    /// common punctuation like “ ” and —, count 1, others like CJK count 2,
+    /// and an emoji counts 2 however many code points it is made of.
+    /// A link counts [`LINK_LENGTH`], since Twitter wraps every link with t.co.
+    ///
+    /// Twitter counts text after NFC normalization, which this does not do,
+    /// so decomposed accents count more than they will when posted.
+    TwitterWeighted,
+    /// Code points, with a link counting [`LINK_LENGTH`] however long it is, as on Mastodon
+    Mastodon,
+    /// GSM-7 septets, as an SMS is sent when every character has one:
+    /// 1 each, or 2 for those in its extension table like `€`.
+    /// Characters GSM-7 doesn't have count their UTF-16 code units;
+    /// see `sms::segment_options` for counting in the encoding a message is sent in
+    Gsm7,
+}
+
+// none of these allocate, since character counters in editors run on every keystroke
+impl Counter for CountMode {
+    #[inline]
+    fn count(&self, text: &str) -> usize {
+        match self {
+            CountMode::Bytes => text.len(),
+            CountMode::Utf16 => text.chars().map(char::len_utf16).sum(),
+            CountMode::Graphemes => text.graphemes(true).count(),
+            CountMode::TwitterWeighted => {
+                with_short_links(text, |text| text.graphemes(true).map(twitter_weight).sum())
+            }
+            CountMode::Mastodon => with_short_links(text, |text| text.chars().count()),
+            CountMode::Gsm7 => text
+                .chars()
+                .map(|c| sms::gsm7_septets(c).unwrap_or_else(|| c.len_utf16()))
+                .sum(),
+        }
+    }
+}
+
+/// How long any link is on Twitter, as shortened by t.co, and on Mastodon.
+pub const LINK_LENGTH: usize = 23;
+
+// `text` measured with `weigh`, except for links, which count `LINK_LENGTH`
+#[inline]
+fn with_short_links(text: &str, weigh: impl Fn(&str) -> usize) -> usize {
+    let (mut counted_to, mut counted) = (0, 0);
+    for link in entities::links(text) {
+        counted += weigh(&text[counted_to..link.start]) + LINK_LENGTH;
+        counted_to = link.end;
+    }
+
+    counted + weigh(&text[counted_to..])
+}
+
+fn twitter_weight(grapheme: &str) -> usize {
+    if is_emoji(grapheme) {
+        return 2;
+    }
+
+    grapheme
+        .chars()
+        .map(|c| match c as u32 {
+            // the ranges twitter-text weighs at 100 rather than 200
+            0..=0x10FF | 0x2000..=0x200D | 0x2010..=0x201F | 0x2032..=0x2037 => 1,
+            _ => 2,
+        })
+        .sum()
+}
+
+// whether a grapheme is an emoji, including sequences like flags, keycaps,
+// skin tones, and ZWJ families
+pub(crate) fn is_emoji(grapheme: &str) -> bool {
+    let mut chars = grapheme.chars();
+    let first = chars.next().map_or(0, |c| c as u32);
+
+    let pictograph = matches!(
+        first,
+        0x1F000..=0x1FAFF | 0x2300..=0x23FF | 0x2600..=0x27BF | 0x2B00..=0x2BFF
+    );
+
+    pictograph || chars.any(|c| c == '\u{FE0F}' || c == '\u{20E3}')
+}
+
+impl FromStr for CountMode {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes" => Ok(CountMode::Bytes),
+            "utf16" => Ok(CountMode::Utf16),
+            "graphemes" => Ok(CountMode::Graphemes),
+            "twitter" => Ok(CountMode::TwitterWeighted),
+            "mastodon" => Ok(CountMode::Mastodon),
+            "gsm7" => Ok(CountMode::Gsm7),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown count mode `{}`, expected bytes, utf16, graphemes, twitter, mastodon, or gsm7.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance;
+    use crate::platform::Platform;
+
+    fn assert_conforms(count: CountMode, platform: Platform) {
+        if let Err(mismatches) = conformance::check(&count, platform) {
+            let mismatches = mismatches
+                .iter()
+                .map(|mismatch| mismatch.to_string())
+                .collect::<Vec<String>>();
+            panic!("{}", mismatches.join("\n"));
+        }
+    }
+
+    #[test]
+    fn it_counts_graphemes_like_bluesky() {
+        assert_conforms(CountMode::Graphemes, Platform::Bluesky);
+    }
+
+    #[test]
+    fn it_counts_weights_like_twitter() {
+        assert_conforms(CountMode::TwitterWeighted, Platform::Twitter);
+        assert_eq!(CountMode::TwitterWeighted.count("❤️ ©"), 4);
+    }
+
+    #[test]
+    fn it_counts_twitter_links_as_shortened() {
+        let link = "https://example.com/2026/10/a-very-long-article-title-that-goes-on-and-on";
+
+        assert_eq!(CountMode::TwitterWeighted.count(link), LINK_LENGTH);
+        assert_eq!(
+            CountMode::TwitterWeighted.count(&format!("see ({}).", link)),
+            5 + LINK_LENGTH + 2
+        );
+        assert_eq!(CountMode::Graphemes.count(link), link.len());
+        assert_eq!(
+            CountMode::Mastodon.count(&format!("日本 👍🏽 {}", link)),
+            6 + LINK_LENGTH
+        );
+    }
+
+    #[test]
+    fn it_counts_bytes() {
+        assert_eq!(CountMode::Bytes.count("café"), 5);
+        assert_eq!(CountMode::Graphemes.count("café"), 4);
+    }
+
+    #[test]
+    fn it_counts_utf16_code_units() {
+        assert_eq!(CountMode::Utf16.count("café"), 4);
+        assert_eq!(CountMode::Utf16.count("日本"), 2);
+        // outside the basic multilingual plane, each code point is a surrogate pair
+        assert_eq!(CountMode::Utf16.count("😀"), 2);
+        assert_eq!(CountMode::Utf16.count("👍🏽"), 4);
+    }
+}
@@ -0,0 +1,590 @@
+// decorations added around the text of each tweet
+
+use crate::count::{CountMode, Counter};
+use crate::numbering::{Numbering, NumberingPosition, Positions};
+use crate::template::{Date, Template, TemplateContext};
+use crate::TweetSplitError;
+
+/// Where a tweet falls in its thread, which is all a decoration may depend on.
+#[derive(Clone, Copy, Debug)]
+pub struct TweetPosition {
+    /// 0-based position of the tweet in the thread
+    pub index: usize,
+    pub total: usize,
+    /// Whether this is the final tweet,
+    /// which can differ from `index + 1 == total` while packing
+    pub is_last: bool,
+    pub today: Option<Date>,
+}
+
+impl TweetPosition {
+    pub fn context(&self) -> TemplateContext {
+        TemplateContext {
+            index: self.index + 1,
+            total: self.total,
+            today: self.today,
+        }
+    }
+}
+
+/// Something added to the text of each tweet, like a counter or hashtags.
+///
+/// Decorations are applied in order, each wrapping the ones before it.
+/// Their room in a tweet is reserved before the text is packed,
+/// so `reserved_width` must be at least what `before` and `after` write for any text,
+/// measured with `count`.
+pub trait TweetDecorator: std::fmt::Debug + Send + Sync {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError>;
+
+    /// Whether `reserved_width` can change with the total number of tweets.
+    fn depends_on_total(&self) -> bool {
+        true
+    }
+
+    /// Append what goes before the tweet's `text` to `tweet`.
+    fn before(
+        &self,
+        _position: &TweetPosition,
+        _text: &str,
+        _tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        Ok(())
+    }
+
+    /// Append what goes after the tweet's `text` to `tweet`.
+    fn after(
+        &self,
+        _position: &TweetPosition,
+        _text: &str,
+        _tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        Ok(())
+    }
+}
+
+/// A marker like "…" at the end of every tweet that the thread continues after,
+/// at the start of every tweet that continues the one before it, or both.
+#[derive(Clone, Debug)]
+pub struct Ellipsis {
+    pub marker: String,
+    /// Whether tweets the thread continues after end with the marker
+    pub trailing: bool,
+    /// Whether tweets after the first start with the marker
+    pub leading: bool,
+}
+
+impl Default for Ellipsis {
+    fn default() -> Self {
+        Self {
+            marker: "…".to_string(),
+            trailing: true,
+            leading: false,
+        }
+    }
+}
+
+impl Ellipsis {
+    fn marks(&self, position: &TweetPosition) -> usize {
+        usize::from(self.trailing && !position.is_last)
+            + usize::from(self.leading && position.index > 0)
+    }
+}
+
+impl TweetDecorator for Ellipsis {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        Ok(self.marks(position) * count.count(&self.marker))
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+
+    fn before(
+        &self,
+        position: &TweetPosition,
+        _text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        if self.leading && position.index > 0 {
+            tweet.push_str(&self.marker);
+        }
+
+        Ok(())
+    }
+
+    fn after(
+        &self,
+        position: &TweetPosition,
+        _text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        if self.trailing && !position.is_last {
+            tweet.push_str(&self.marker);
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashtags appended to some tweets of the thread, like " #rust #cli".
+#[derive(Clone, Debug)]
+pub struct Hashtags {
+    // the tags with their leading spaces
+    rendered: String,
+    positions: Positions,
+}
+
+impl Hashtags {
+    /// Append `tags`, with or without their `#`, to the tweets at `positions`.
+    pub fn new<S: AsRef<str>>(tags: &[S], positions: Positions) -> Self {
+        let mut rendered = String::new();
+
+        for tag in tags {
+            let tag = tag.as_ref().trim().trim_start_matches('#');
+            if !tag.is_empty() {
+                rendered.push_str(" #");
+                rendered.push_str(tag);
+            }
+        }
+
+        Self {
+            rendered,
+            positions,
+        }
+    }
+
+    fn applies(&self, position: &TweetPosition) -> bool {
+        self.positions.includes(position.index, position.is_last)
+    }
+}
+
+impl TweetDecorator for Hashtags {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        Ok(if self.applies(position) {
+            count.count(&self.rendered)
+        } else {
+            0
+        })
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+
+    fn after(
+        &self,
+        position: &TweetPosition,
+        _text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        if self.applies(position) {
+            tweet.push_str(&self.rendered);
+        }
+
+        Ok(())
+    }
+}
+
+/// Room for the mentions like "@ckampfe " that a platform puts before each reply,
+/// on every tweet after the first when a thread is posted as replies.
+#[derive(Clone, Debug)]
+pub struct ReplyMentions {
+    // the mentions, each followed by a space
+    rendered: String,
+    /// Whether to write the mentions too, for platforms that expect them in the text,
+    /// rather than only leave room for them
+    pub render: bool,
+}
+
+impl ReplyMentions {
+    /// Leave room for `accounts`, with or without their `@`, before each reply.
+    pub fn new<S: AsRef<str>>(accounts: &[S], render: bool) -> Self {
+        let mut rendered = String::new();
+
+        for account in accounts {
+            let account = account.as_ref().trim().trim_start_matches('@');
+            if !account.is_empty() {
+                rendered.push('@');
+                rendered.push_str(account);
+                rendered.push(' ');
+            }
+        }
+
+        Self { rendered, render }
+    }
+}
+
+impl TweetDecorator for ReplyMentions {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        Ok(if position.index > 0 {
+            count.count(&self.rendered)
+        } else {
+            0
+        })
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+
+    fn before(
+        &self,
+        position: &TweetPosition,
+        _text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        if self.render && position.index > 0 {
+            tweet.push_str(&self.rendered);
+        }
+
+        Ok(())
+    }
+}
+
+/// Puts a `.` before a tweet that starts with a @mention,
+/// which would otherwise post as a reply to that account.
+///
+/// The text is only known after packing, so every tweet reserves room for the `.`.
+#[derive(Clone, Copy, Debug)]
+pub struct EscapeMentions;
+
+impl TweetDecorator for EscapeMentions {
+    fn reserved_width(
+        &self,
+        _position: &TweetPosition,
+        _count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        Ok(1)
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+
+    fn before(
+        &self,
+        _position: &TweetPosition,
+        text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        if tweet.is_empty() && text.starts_with('@') {
+            tweet.push('.');
+        }
+
+        Ok(())
+    }
+}
+
+/// Puts U+2060 WORD JOINER before a tweet after the first that starts with
+/// punctuation some clients render oddly at the start of a line,
+/// like a "…" or "—" that a break left at the front of its tweet.
+/// A tweet whose prefix or counter comes before that punctuation is left alone.
+///
+/// The joiner is invisible, but it counts, so every tweet after the first makes room for one.
+#[derive(Clone, Copy, Debug)]
+pub struct WordJoiner;
+
+impl WordJoiner {
+    const JOINER: &'static str = "\u{2060}";
+
+    // what a continuing tweet shouldn't start with
+    const RISKY: &'static str = "…—–‐‑-.,;:!?)]}»”’";
+}
+
+impl TweetDecorator for WordJoiner {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        Ok(if position.index > 0 {
+            count.count(Self::JOINER)
+        } else {
+            0
+        })
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+
+    fn before(
+        &self,
+        position: &TweetPosition,
+        text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        let risky = text.chars().next().is_some_and(|c| Self::RISKY.contains(c));
+
+        if position.index > 0 && tweet.is_empty() && risky {
+            tweet.push_str(Self::JOINER);
+        }
+
+        Ok(())
+    }
+}
+
+// the decorations `SplitOptions` has fields for, alongside its other decorators
+#[derive(Debug)]
+pub(crate) enum Decoration<'a> {
+    Custom(&'a dyn TweetDecorator),
+    Prefix(&'a Template),
+    Suffix(&'a Template),
+    Numbering(&'a Numbering),
+    EndMarker(&'a str),
+}
+
+impl TweetDecorator for Decoration<'_> {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        match self {
+            Decoration::Custom(decorator) => decorator.reserved_width(position, count),
+            Decoration::Prefix(template) | Decoration::Suffix(template) => {
+                template.rendered_len(&position.context(), count)
+            }
+            Decoration::Numbering(numbering) => Ok(numbering_at(numbering, position)
+                .map(|numbering| numbering.reserved_width(position.total, count))
+                .unwrap_or(0)),
+            Decoration::EndMarker(marker) if position.is_last => Ok(1 + count.count(marker)),
+            Decoration::EndMarker(_) => Ok(0),
+        }
+    }
+
+    fn depends_on_total(&self) -> bool {
+        match self {
+            Decoration::Custom(decorator) => decorator.depends_on_total(),
+            Decoration::Prefix(template) | Decoration::Suffix(template) => {
+                template.depends_on_total()
+            }
+            Decoration::Numbering(_) => true,
+            Decoration::EndMarker(_) => false,
+        }
+    }
+
+    fn before(
+        &self,
+        position: &TweetPosition,
+        text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        match self {
+            Decoration::Custom(decorator) => decorator.before(position, text, tweet),
+            Decoration::Prefix(template) => template.render_into(&position.context(), tweet),
+            Decoration::Numbering(numbering) => {
+                if let Some(numbering) = numbering_at(numbering, position)
+                    .filter(|numbering| numbering.position == NumberingPosition::Prefix)
+                {
+                    numbering.render_into(position.index + 1, position.total, tweet);
+                }
+                Ok(())
+            }
+            Decoration::Suffix(_) | Decoration::EndMarker(_) => Ok(()),
+        }
+    }
+
+    fn after(
+        &self,
+        position: &TweetPosition,
+        text: &str,
+        tweet: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        match self {
+            Decoration::Custom(decorator) => decorator.after(position, text, tweet),
+            Decoration::Suffix(template) => template.render_into(&position.context(), tweet),
+            Decoration::Numbering(numbering) => {
+                if let Some(numbering) = numbering_at(numbering, position)
+                    .filter(|numbering| numbering.position == NumberingPosition::Suffix)
+                {
+                    numbering.render_into(position.index + 1, position.total, tweet);
+                }
+                Ok(())
+            }
+            Decoration::EndMarker(marker) => {
+                if position.is_last {
+                    tweet.push(' ');
+                    tweet.push_str(marker);
+                }
+                Ok(())
+            }
+            Decoration::Prefix(_) => Ok(()),
+        }
+    }
+}
+
+fn numbering_at<'a>(numbering: &'a Numbering, position: &TweetPosition) -> Option<&'a Numbering> {
+    Some(numbering).filter(|numbering| {
+        numbering
+            .positions
+            .includes(position.index, position.is_last)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(index: usize, total: usize) -> TweetPosition {
+        TweetPosition {
+            index,
+            total,
+            is_last: index + 1 == total,
+            today: None,
+        }
+    }
+
+    fn decorate(decorator: &dyn TweetDecorator, position: &TweetPosition, text: &str) -> String {
+        let mut tweet = String::new();
+        decorator.before(position, text, &mut tweet).unwrap();
+        tweet.push_str(text);
+        decorator.after(position, text, &mut tweet).unwrap();
+
+        tweet
+    }
+
+    #[test]
+    fn it_adds_ellipses_to_all_but_the_last_tweet() {
+        let ellipsis = Ellipsis::default();
+
+        assert_eq!(decorate(&ellipsis, &position(0, 2), "more"), "more…");
+        assert_eq!(decorate(&ellipsis, &position(1, 2), "done"), "done");
+        assert_eq!(
+            ellipsis
+                .reserved_width(&position(0, 2), CountMode::Bytes)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            ellipsis
+                .reserved_width(&position(0, 2), CountMode::Graphemes)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn it_adds_ellipses_before_continuing_tweets() {
+        let ellipsis = Ellipsis {
+            leading: true,
+            ..Ellipsis::default()
+        };
+        let leading = Ellipsis {
+            trailing: false,
+            ..ellipsis.clone()
+        };
+
+        assert_eq!(decorate(&ellipsis, &position(0, 3), "one"), "one…");
+        assert_eq!(decorate(&ellipsis, &position(1, 3), "two"), "…two…");
+        assert_eq!(decorate(&ellipsis, &position(2, 3), "three"), "…three");
+        assert_eq!(decorate(&leading, &position(1, 3), "two"), "…two");
+        assert_eq!(
+            ellipsis
+                .reserved_width(&position(1, 3), CountMode::Bytes)
+                .unwrap(),
+            6
+        );
+    }
+
+    #[test]
+    fn it_appends_hashtags() {
+        let hashtags = Hashtags::new(&["#rust", "cli", " "], "last".parse().unwrap());
+
+        assert_eq!(decorate(&hashtags, &position(0, 2), "first"), "first");
+        assert_eq!(
+            decorate(&hashtags, &position(1, 2), "last"),
+            "last #rust #cli"
+        );
+        assert_eq!(
+            hashtags
+                .reserved_width(&position(0, 2), CountMode::Bytes)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            hashtags
+                .reserved_width(&position(1, 2), CountMode::Bytes)
+                .unwrap(),
+            11
+        );
+    }
+
+    #[test]
+    fn it_leaves_room_for_reply_mentions() {
+        let reserved = ReplyMentions::new(&["@ckampfe", "rustlang"], false);
+        let rendered = ReplyMentions::new(&["ckampfe"], true);
+
+        assert_eq!(decorate(&reserved, &position(1, 2), "reply"), "reply");
+        assert_eq!(
+            reserved
+                .reserved_width(&position(1, 2), CountMode::Bytes)
+                .unwrap(),
+            19
+        );
+        assert_eq!(
+            reserved
+                .reserved_width(&position(0, 2), CountMode::Bytes)
+                .unwrap(),
+            0
+        );
+        assert_eq!(decorate(&rendered, &position(0, 2), "first"), "first");
+        assert_eq!(
+            decorate(&rendered, &position(1, 2), "reply"),
+            "@ckampfe reply"
+        );
+    }
+
+    #[test]
+    fn it_escapes_leading_mentions() {
+        assert_eq!(
+            decorate(&EscapeMentions, &position(0, 1), "@ckampfe hi"),
+            ".@ckampfe hi"
+        );
+        assert_eq!(
+            decorate(&EscapeMentions, &position(0, 1), "hi @ckampfe"),
+            "hi @ckampfe"
+        );
+    }
+
+    #[test]
+    fn it_joins_continuing_tweets_to_leading_punctuation() {
+        assert_eq!(
+            decorate(&WordJoiner, &position(1, 2), "— and then"),
+            "\u{2060}— and then"
+        );
+        assert_eq!(
+            decorate(&WordJoiner, &position(1, 2), "and then"),
+            "and then"
+        );
+        assert_eq!(decorate(&WordJoiner, &position(0, 2), "…so"), "…so");
+
+        let mut prefixed = "2/2 ".to_string();
+        WordJoiner
+            .before(&position(1, 2), "— and then", &mut prefixed)
+            .unwrap();
+        assert_eq!(prefixed, "2/2 ");
+        assert_eq!(
+            WordJoiner
+                .reserved_width(&position(1, 2), CountMode::Utf16)
+                .unwrap(),
+            1
+        );
+    }
+}
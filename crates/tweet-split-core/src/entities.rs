@@ -0,0 +1,170 @@
+// links, mentions, hashtags, and cashtags in a tweet, which posting clients
+// need the positions of, like Bluesky's facets
+
+use crate::count::{CountMode, Counter};
+use lazy_static::*;
+use regex::Regex;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    Url,
+    /// `@name`, or `@handle.example.com` on Bluesky
+    Mention,
+    Hashtag,
+    /// A ticker symbol like `$TSLA`
+    Cashtag,
+}
+
+impl EntityKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntityKind::Url => "url",
+            EntityKind::Mention => "mention",
+            EntityKind::Hashtag => "hashtag",
+            EntityKind::Cashtag => "cashtag",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    /// Where the entity is in the tweet, as UTF-8 byte offsets
+    pub bytes: Range<usize>,
+    /// Where the entity is in the tweet, counted like its length
+    pub range: Range<usize>,
+}
+
+impl Entity {
+    pub fn text<'a>(&self, tweet: &'a str) -> &'a str {
+        &tweet[self.bytes.clone()]
+    }
+}
+
+/// Every entity in `tweet`, in order, with ranges counted with `count`.
+///
+/// Mentions, hashtags, and cashtags inside a link, like the `#section` of a URL,
+/// are part of the link only.
+pub fn extract(tweet: &str, count: CountMode) -> Vec<Entity> {
+    let urls = links(tweet).collect::<Vec<Range<usize>>>();
+    let in_url = |bytes: &Range<usize>| {
+        urls.iter()
+            .any(|url| bytes.start < url.end && url.start < bytes.end)
+    };
+
+    let mut found = urls
+        .iter()
+        .map(|bytes| (EntityKind::Url, bytes.clone()))
+        .collect::<Vec<(EntityKind, Range<usize>)>>();
+
+    let tags = [
+        (EntityKind::Mention, &*MENTION),
+        (EntityKind::Hashtag, &*HASHTAG),
+        (EntityKind::Cashtag, &*CASHTAG),
+    ];
+    for (kind, pattern) in tags.iter() {
+        for captures in pattern.captures_iter(tweet) {
+            let tag = captures.get(1).expect("tag patterns capture the tag");
+            let text = tag.as_str().trim_end_matches(['.', '-']);
+            let bytes = tag.start()..tag.start() + text.len();
+
+            if !in_url(&bytes) {
+                found.push((*kind, bytes));
+            }
+        }
+    }
+
+    found.sort_by_key(|(_, bytes)| bytes.start);
+
+    // count each stretch between entities once, rather than from the start every time
+    let (mut counted_to, mut counted) = (0, 0);
+    found
+        .into_iter()
+        .map(|(kind, bytes)| {
+            counted += count.count(&tweet[counted_to..bytes.start]);
+            let start = counted;
+            counted += count.count(&tweet[bytes.clone()]);
+            counted_to = bytes.end;
+
+            Entity {
+                kind,
+                bytes,
+                range: start..counted,
+            }
+        })
+        .collect()
+}
+
+// the byte ranges of the links in `text`, in order
+pub(crate) fn links(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    URL.find_iter(text).map(|url| {
+        let link = url.as_str().trim_end_matches(TRAILING_URL_PUNCTUATION);
+        url.start()..url.start() + link.len()
+    })
+}
+
+const TRAILING_URL_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', '\'', '"'];
+
+lazy_static! {
+    // an ASCII word boundary, since a Unicode one sends non-ASCII text to a slower engine
+    // that allocates on every search, and counting runs on every keystroke
+    static ref URL: Regex = Regex::new(r"(?i)(?-u:\b)(?:https?://|www\.)\S+").unwrap();
+    static ref MENTION: Regex = Regex::new(r"(?:^|[^\w@])(@\w[\w.-]*)").unwrap();
+    static ref HASHTAG: Regex = Regex::new(r"(?:^|[^\w&#])(#\w*[\p{L}_]\w*)").unwrap();
+    static ref CASHTAG: Regex =
+        Regex::new(r"(?:^|[^\w$])(\$[A-Za-z]{1,6}(?:[._][A-Za-z]{1,2})?)\b").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(tweet: &str) -> Vec<(EntityKind, &str)> {
+        extract(tweet, CountMode::Bytes)
+            .iter()
+            .map(|entity| (entity.kind, entity.text(tweet)))
+            .collect()
+    }
+
+    #[test]
+    fn it_extracts_entities_in_order() {
+        let tweet =
+            "Thanks @alice and @bob.bsky.social! $TSLA #rustlang (see https://example.com/a?b=1).";
+
+        assert_eq!(
+            texts(tweet),
+            vec![
+                (EntityKind::Mention, "@alice"),
+                (EntityKind::Mention, "@bob.bsky.social"),
+                (EntityKind::Cashtag, "$TSLA"),
+                (EntityKind::Hashtag, "#rustlang"),
+                (EntityKind::Url, "https://example.com/a?b=1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_leaves_tags_inside_links_to_the_link() {
+        let tweet = "www.example.com/@someone#section and $5, me@example.com, issue #42";
+
+        assert_eq!(
+            texts(tweet),
+            vec![(EntityKind::Url, "www.example.com/@someone#section")]
+        );
+    }
+
+    #[test]
+    fn it_counts_ranges_in_the_count_mode() {
+        let tweet = "日本 #東京 @ok";
+
+        let bytes = extract(tweet, CountMode::Bytes);
+        let graphemes = extract(tweet, CountMode::Graphemes);
+
+        assert_eq!(bytes[0].bytes, 7..14);
+        assert_eq!(bytes[0].range, 7..14);
+        assert_eq!(graphemes[0].range, 3..6);
+        assert_eq!(graphemes[1].range, 7..10);
+        assert_eq!(graphemes[1].text(tweet), "@ok");
+    }
+}
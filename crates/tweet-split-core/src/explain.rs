@@ -0,0 +1,165 @@
+// longer explanations of each error code, like `rustc --explain`
+
+/// Every error code, in order
+pub const CODES: [&str; 7] = [
+    "TS001", "TS002", "TS003", "TS004", "TS005", "TS006", "TS007",
+];
+
+/// A description of the error with `code`, its usual causes, and how to fix it.
+///
+/// Codes are matched case-insensitively, so `ts001` finds `TS001`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    let explanation = match code.to_ascii_uppercase().as_str() {
+        "TS001" => TS001,
+        "TS002" => TS002,
+        "TS003" => TS003,
+        "TS004" => TS004,
+        "TS005" => TS005,
+        "TS006" => TS006,
+        "TS007" => TS007,
+        _ => return None,
+    };
+
+    Some(explanation)
+}
+
+const TS001: &str = "\
+TS001: the tweet length is too short
+
+The text could not be split into tweets of the maximum length.
+
+Common causes:
+- the prefix, suffix, counter, and end marker leave no room for text
+- a paragraph is longer than a tweet with `--paragraphs keep`
+- the sign-off doesn't fit in the final tweet
+
+Fixes:
+- raise the limit with `-l`
+- shorten or drop decorations (`--prefix`, `--suffix`, `-n`, `--end-marker`)
+- use `--paragraphs auto` to reflow paragraphs that don't fit
+- loosen `--keep-together` patterns that match long spans";
+
+const TS002: &str = "\
+TS002: a variable is not defined
+
+The text refers to `${NAME}`, but NAME is neither defined with `-D` nor set
+in the environment.
+
+Fixes:
+- define it with `-D NAME=value`
+- export it in the environment before running ts
+- write `$${NAME}` to keep a literal `${NAME}` in the text";
+
+const TS003: &str = "\
+TS003: a template is invalid
+
+A `--prefix` or `--suffix` template could not be parsed or rendered.
+
+Common causes:
+- an unclosed `{{` or a stray `}`
+- a placeholder other than `index`, `total`, integer arithmetic on them,
+  or `today:FORMAT`
+- an unknown `%` directive in a date format
+- division by zero in an expression
+
+Fixes:
+- write a literal brace as `\\{` or `\\}`
+- check the placeholder names, e.g. `{{index}}/{{total}}` or `{{today:%Y-%m-%d}}`";
+
+const TS004: &str = "\
+TS004: an option has an invalid value
+
+An option was given a value it doesn't recognize, like an unknown numbering
+style or paragraph mode.
+
+Fixes:
+- check `ts --help` for the accepted values";
+
+const TS005: &str = "\
+TS005: the input is invalid
+
+A structured input, like a `--batch` file of drafts, could not be read.
+
+Common causes:
+- a line of a JSON Lines file that isn't a JSON object
+- a CSV file whose last quoted field is never closed
+- a draft without a `text` field
+
+Fixes:
+- the error names the line or record; fix it there";
+
+const TS006: &str = "\
+TS006: a word is longer than a tweet
+
+By default the text is split only on whitespace, and a word, like a long URL,
+is longer than the room left for text in its tweet.
+
+Fixes:
+- raise the limit with `-l`; the error suggests the smallest that fits
+- shorten or drop decorations, which take room from every tweet they're on
+- shorten the word, e.g. with a link shortener or `--shorten`
+- split the word between characters with `--overflow split`";
+
+const TS007: &str = "\
+TS007: a tweet breaks a platform rule
+
+With `--validate PLATFORM --strict`, a tweet fits the length limit but
+breaks another of the platform's rules, which would make posting it fail.
+
+Common causes:
+- too many @mentions, #hashtags, or links in one tweet
+- an invisible character the platform rejects, like a byte order mark
+  (U+FEFF) or a directional override (U+202A to U+202E), often pasted in
+  from another app
+- a tweet with the same text as an earlier one, which Twitter refuses
+
+Fixes:
+- the error names the tweet and the rule; spread mentions, hashtags, or
+  links over more tweets, or remove the character
+- make repeated tweets differ invisibly with `--vary-duplicates`
+- drop `--strict` to post anyway and only print warnings";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TweetSplitError;
+
+    #[test]
+    fn it_explains_every_code() {
+        let errors = [
+            TweetSplitError::MaxTweetLengthTooShort {
+                details: String::new(),
+            },
+            TweetSplitError::UndefinedVariable {
+                name: String::new(),
+            },
+            TweetSplitError::InvalidTemplate {
+                details: String::new(),
+            },
+            TweetSplitError::InvalidOption {
+                details: String::new(),
+            },
+            TweetSplitError::InvalidInput {
+                details: String::new(),
+            },
+            TweetSplitError::WordTooLong {
+                word: String::new(),
+                offset: 0,
+                length: 0,
+                budget: 0,
+            },
+            TweetSplitError::RuleViolation {
+                details: String::new(),
+            },
+        ];
+
+        let codes = errors.iter().map(|error| error.code()).collect::<Vec<_>>();
+
+        assert_eq!(codes, CODES);
+        for code in CODES.iter() {
+            assert!(explain(code).unwrap().starts_with(code));
+        }
+        assert_eq!(explain("ts003"), explain("TS003"));
+        assert_eq!(explain("TS999"), None);
+    }
+}
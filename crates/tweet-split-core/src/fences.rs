@@ -0,0 +1,190 @@
+// keeping fenced code blocks rendering in every tweet, for platforms that render
+// Markdown like Discord: a tweet that ends inside a block closes it, and the next
+// tweet reopens it with the same opening line, language tag and all
+
+use crate::count::{CountMode, Counter};
+use crate::decorate::{TweetDecorator, TweetPosition};
+use crate::thread::Thread;
+use crate::{SplitOptions, TweetSplitError};
+use std::sync::Arc;
+
+/// Like `split_thread`, but closing a fenced code block at the end of any tweet
+/// that ends inside it, and reopening it at the start of the next.
+///
+/// The text is only known after packing, so every tweet after the first reserves room
+/// for the input's longest opening line, and every tweet before the last for a closing one.
+/// Decorations go around the fences, so they stay outside the block.
+pub fn split_thread(input: &str, options: &SplitOptions) -> Result<Thread, TweetSplitError> {
+    let mut fence = None;
+    let mut room = FenceRoom::default();
+
+    for line in input.lines() {
+        fence = next_fence(fence, line);
+        if let Some(fence) = &fence {
+            room.opener = room.opener.max(options.count.count(&fence.opener) + 1);
+            room.closer = room.closer.max(options.count.count(&fence.marker) + 1);
+        }
+    }
+
+    if room.opener == 0 {
+        return crate::split_thread(input, options);
+    }
+
+    let mut reserving = options.clone();
+    reserving.decorators.push(Arc::new(room));
+    let thread = crate::split_thread(input, &reserving)?;
+
+    let total = thread.texts.len();
+    let mut fence: Option<Fence> = None;
+    let mut tweets = Vec::with_capacity(total);
+
+    for (index, text) in thread.texts.iter().enumerate() {
+        let mut lines: Vec<&str> = text.lines().collect();
+        let mut fenced = String::with_capacity(text.len());
+
+        // a tweet starting with the line that closes the block only needs it closed
+        if let (Some(open), Some(first)) = (fence.clone(), lines.first()) {
+            if next_fence(Some(open.clone()), first).is_none() {
+                lines.remove(0);
+                fence = None;
+            } else {
+                fenced.push_str(&open.opener);
+                fenced.push('\n');
+            }
+        }
+
+        let mut opened_by_last = false;
+        for line in &lines {
+            let was_open = fence.is_some();
+            fence = next_fence(fence, line);
+            opened_by_last = !was_open && fence.is_some();
+        }
+
+        let is_last = index + 1 == total;
+        // and one ending with the line that opens it only needs it opened in the next
+        if opened_by_last && !is_last && lines.len() > 1 {
+            lines.pop();
+        }
+        fenced.push_str(&lines.join("\n"));
+
+        if let Some(open) = fence.as_ref().filter(|_| !is_last && !opened_by_last) {
+            fenced.push('\n');
+            fenced.push_str(&open.marker);
+        }
+
+        tweets.push(options.render(index, total, &fenced)?);
+    }
+
+    Ok(Thread { tweets, ..thread })
+}
+
+// an open fenced code block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Fence {
+    // the run of backticks or tildes that opened it, which a closing line repeats
+    marker: String,
+    // its whole opening line, like "```rust"
+    opener: String,
+}
+
+// the fence open after `line`, given the one open before it
+pub(crate) fn next_fence(open: Option<Fence>, line: &str) -> Option<Fence> {
+    let line = line.trim();
+
+    match open {
+        Some(fence) => {
+            let closes = line.len() >= fence.marker.len()
+                && line.chars().all(|c| fence.marker.starts_with(c));
+            if closes {
+                None
+            } else {
+                Some(fence)
+            }
+        }
+        None => {
+            let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+            let marker_len = line.len() - line.trim_start_matches(fence_char).len();
+            let info = &line[marker_len..];
+
+            // an info string with a backtick is inline code, not a fence
+            if marker_len < 3 || (fence_char == '`' && info.contains('`')) {
+                return None;
+            }
+
+            Some(Fence {
+                marker: line[..marker_len].to_string(),
+                opener: line.to_string(),
+            })
+        }
+    }
+}
+
+// room for a reopened fence and its newline in tweets after the first,
+// and for a newline and a closing fence in tweets before the last
+#[derive(Clone, Copy, Debug, Default)]
+struct FenceRoom {
+    opener: usize,
+    closer: usize,
+}
+
+impl TweetDecorator for FenceRoom {
+    fn reserved_width(
+        &self,
+        position: &TweetPosition,
+        _count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        let opener = if position.index > 0 { self.opener } else { 0 };
+        let closer = if position.is_last { 0 } else { self.closer };
+
+        Ok(opener + closer)
+    }
+
+    fn depends_on_total(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SplitStrategy;
+
+    #[test]
+    fn it_reopens_a_block_split_across_tweets() {
+        let input = "look:\n```rust\nlet a = 1;\nlet b = 2;\nlet c = 3;\n```\ndone";
+        let mut options = SplitOptions::new(33);
+        options.strategy = SplitStrategy::Lines;
+        let thread = split_thread(input, &options).unwrap();
+
+        assert_eq!(
+            thread.tweets,
+            vec![
+                "look:\n```rust\nlet a = 1;\n```",
+                "```rust\nlet b = 2;\nlet c = 3;\n```",
+                "done",
+            ]
+        );
+        assert!(thread.tweets.iter().all(|tweet| tweet.len() <= 33));
+
+        let words = split_thread(input, &SplitOptions::new(33)).unwrap();
+        assert_eq!(
+            words.tweets,
+            vec![
+                "look:\n```rust\nlet a = 1;\nlet\n```",
+                "```rust\nb = 2;\nlet c = 3;\n```",
+                "done",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_leaves_text_without_fences_alone() {
+        let input = "some `inline` code and ``` ` ``` that isn't a block";
+        let options = SplitOptions::new(20);
+
+        assert_eq!(
+            split_thread(input, &options).unwrap().tweets,
+            crate::split_thread(input, &options).unwrap().tweets
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// spans of text that should not be split across tweets
+
+use lazy_static::*;
+use regex::Regex;
+
+/// Initials and initialisms, like "J. R. R. Tolkien" or "U. S. A."
+pub fn initials() -> Regex {
+    INITIALS.clone()
+}
+
+/// Numbers with their units and currency symbols, like "3 000 €", "$ 20", "10 MB", or "5 pm"
+pub fn numbers_with_units() -> Regex {
+    NUMBERS_WITH_UNITS.clone()
+}
+
+/// Markdown list items, so each bullet stays on one tweet when it fits
+pub fn list_items() -> Regex {
+    LIST_ITEMS.clone()
+}
+
+/// For each word but the last, whether a tweet may end after it.
+///
+/// A break is forbidden when the whitespace after a word falls inside a match
+/// of any of `patterns`.
+pub(crate) fn breakable(input: &str, words: &[(usize, usize)], patterns: &[Regex]) -> Vec<bool> {
+    let mut breakable = vec![true; words.len().saturating_sub(1)];
+
+    for pattern in patterns {
+        for span in pattern.find_iter(input) {
+            // the first gap that could start inside this span
+            let first = words.partition_point(|(_, end)| *end <= span.start());
+
+            for (gap, window) in words.windows(2).enumerate().skip(first) {
+                let (gap_start, gap_end) = (window[0].1, window[1].0);
+
+                if gap_start >= span.end() {
+                    break;
+                }
+                if gap_start >= span.start() && gap_end <= span.end() {
+                    breakable[gap] = false;
+                }
+            }
+        }
+    }
+
+    breakable
+}
+
+lazy_static! {
+    static ref INITIALS: Regex = Regex::new(r"\b(?:\p{Lu}\.[ \t]+)+\p{Lu}[\p{L}.]*").unwrap();
+    static ref LIST_ITEMS: Regex = Regex::new(r"(?m)^[ \t]*(?:[-*+]|\d+[.)])[ \t].*$").unwrap();
+    static ref NUMBERS_WITH_UNITS: Regex = Regex::new(concat!(
+        // a leading currency symbol, as in "$ 20"
+        r"(?:\p{Sc}[ \t]*)?",
+        // digits, with thousands optionally grouped by spaces, as in "3 000"
+        r"\b\d+(?:[.,]\d+)?(?:[ \t\u{a0}\u{202f}]\d{3}(?:[.,]\d+)?)*",
+        // a trailing unit or currency symbol
+        r"(?:[ \t\u{a0}\u{202f}]*(?:\p{Sc}|%|‰|°[CF]?|[ap]\.m\.",
+        r"|(?:[kMGTP]i?B|[kKMGT]?b|bytes?|[kMG]?Hz|[mk]?W|kWh|mAh|V|[mkc]?m|mi|ft|km/h|mph",
+        r"|[mk]?g|lbs?|oz|[mc]?[lL]|ms|s|sec|min|h|hrs?|[AaPp][Mm]|px|pt|em|USD|EUR|GBP|JPY)\b))?",
+    ))
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<(usize, usize)> {
+        Regex::new(r"\S+")
+            .unwrap()
+            .find_iter(input)
+            .map(|word| (word.start(), word.end()))
+            .collect()
+    }
+
+    #[test]
+    fn it_forbids_breaks_inside_initials() {
+        let input = "by J. R. R. Tolkien in the U. S. A. today";
+
+        let breakable = breakable(input, &words(input), &[initials()]);
+
+        assert_eq!(
+            breakable,
+            vec![true, false, false, false, true, true, true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn it_forbids_breaks_between_numbers_and_units() {
+        let input = "pay 3 000 € or $ 20 for 10 MB by 5 pm, 7 a.m. at 20 °C, not 5 cats";
+
+        let breakable = breakable(input, &words(input), &[numbers_with_units()]);
+        let glued = words(input)
+            .windows(2)
+            .zip(breakable)
+            .filter(|(_, breakable)| !breakable)
+            .map(|(window, _)| &input[window[0].0..window[1].1])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            glued,
+            vec!["3 000", "000 €", "$ 20", "10 MB", "5 pm,", "7 a.m.", "20 °C,"]
+        );
+    }
+
+    #[test]
+    fn it_forbids_breaks_inside_list_items() {
+        let input = "- one two\n- three four";
+
+        let breakable = breakable(input, &words(input), &[list_items()]);
+
+        assert_eq!(breakable, vec![false, false, true, false, false]);
+    }
+
+    #[test]
+    fn it_allows_every_break_without_patterns() {
+        let input = "a b c";
+
+        assert_eq!(breakable(input, &words(input), &[]), vec![true, true]);
+    }
+}
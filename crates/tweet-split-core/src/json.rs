@@ -0,0 +1,325 @@
+// a small JSON reader and writer, enough for draft files and structured output
+
+use crate::TweetSplitError;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Members in the order they appear
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(source: &str) -> Result<Self, TweetSplitError> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+
+        if parser.position < source.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    /// The member named `key`, if this is an object that has one.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(boolean) => write!(f, "{}", boolean),
+            Json::Number(number) if number.is_finite() => write!(f, "{}", number),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(string) => write_string(f, string),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, string: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in string.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> TweetSplitError {
+        TweetSplitError::InvalidInput {
+            details: format!("Invalid JSON at byte {}: {}.", self.position, message),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !matches!(c, ' ' | '\t' | '\n' | '\r') {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), TweetSplitError> {
+        if self.source[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", literal)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, TweetSplitError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, TweetSplitError> {
+        let start = self.position;
+
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                break;
+            }
+            self.position += 1;
+        }
+
+        self.source[start..self.position]
+            .parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, TweetSplitError> {
+        self.expect("\"")?;
+        let mut string = String::new();
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.next() {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('/') => string.push('/'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('n') => string.push('\n'),
+                    Some('r') => string.push('\r'),
+                    Some('t') => string.push('\t'),
+                    Some('u') => string.push(self.unicode_escape()?),
+                    _ => return Err(self.error("invalid escape")),
+                },
+                Some(c) => string.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // the character for a `\uXXXX` escape, whose `\u` has been consumed,
+    // combining UTF-16 surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, TweetSplitError> {
+        let high = self.hex4()?;
+
+        let code_point = if (0xd800..0xdc00).contains(&high) {
+            self.expect("\\u")?;
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("invalid surrogate pair"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+
+        std::char::from_u32(code_point).ok_or_else(|| self.error("invalid code point"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, TweetSplitError> {
+        let digits = self
+            .source
+            .get(self.position..self.position + 4)
+            .ok_or_else(|| self.error("truncated unicode escape"))?;
+        let value =
+            u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid unicode escape"))?;
+
+        self.position += 4;
+        Ok(value)
+    }
+
+    fn array(&mut self) -> Result<Json, TweetSplitError> {
+        self.expect("[")?;
+        let mut values = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, TweetSplitError> {
+        self.expect("{")?;
+        let mut members = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            members.push((name, self.value()?));
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_values() {
+        let json = Json::parse(
+            r#" {"text": "hi \"there\"\né\ud83e\uddf5", "n": -1.5e2, "ok": true, "tags": ["a", null], "empty": {}} "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            json.get("text").and_then(Json::as_str),
+            Some("hi \"there\"\né🧵")
+        );
+        assert_eq!(json.get("n"), Some(&Json::Number(-150.0)));
+        assert_eq!(json.get("ok"), Some(&Json::Bool(true)));
+        assert_eq!(
+            json.get("tags"),
+            Some(&Json::Array(vec![
+                Json::String("a".to_string()),
+                Json::Null
+            ]))
+        );
+        assert_eq!(json.get("empty"), Some(&Json::Object(vec![])));
+    }
+
+    #[test]
+    fn it_rejects_invalid_json() {
+        for source in &["", "{", "[1,]", "{\"a\" 1}", "\"unterminated", "tru", "1 2"] {
+            assert!(
+                Json::parse(source).is_err(),
+                "{:?} should not parse",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn it_writes_what_it_reads() {
+        let source = r#"{"text":"tab\tquote\"é","index":1,"list":[true,false,null]}"#;
+
+        assert_eq!(Json::parse(source).unwrap().to_string(), source);
+    }
+}
@@ -0,0 +1,1936 @@
+// take a body of text
+// split it into pieces that are `max_tweet_length` or less
+// splits are only valid on whitespace
+// try to preserve whitespace
+// if whitespace falls on a split, discard it
+// if a word is longer than a tweet, follow the overflow policy,
+// which can split it on a grapheme boundary
+// reserve room for any prefix and suffix rendered around each tweet
+// trim trailing whitespace from text
+// no files, clock, or randomness: the date for `today` comes from the caller,
+// and hash maps are only looked up, never iterated into output.
+// with the `pure` feature, environment variables aren't read either,
+// so the output depends on nothing but the arguments (see tests/pure.rs)
+
+use lazy_static::*;
+use regex::Regex;
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod a11y;
+pub mod batch;
+pub mod changelog;
+pub mod conformance;
+pub mod confusables;
+pub mod count;
+pub mod decorate;
+pub mod entities;
+pub mod explain;
+pub mod fences;
+pub mod glue;
+pub mod id;
+pub mod json;
+pub mod numbering;
+pub mod platform;
+pub mod preprocess;
+pub mod sections;
+pub mod shorten;
+pub mod sms;
+pub mod template;
+pub mod thread;
+pub mod validate;
+pub mod wrap;
+
+use count::{CountMode, Counter};
+use decorate::{Decoration, ReplyMentions, TweetDecorator, TweetPosition};
+use numbering::Numbering;
+use template::{Date, Template, TweetTemplate};
+use thread::{Thread, Tweet};
+
+#[derive(Clone, Debug)]
+pub enum TweetSplitError {
+    MaxTweetLengthTooShort {
+        details: String,
+    },
+    /// A word is longer than the room for text in its tweet,
+    /// so the text can't be split only on whitespace
+    WordTooLong {
+        word: String,
+        /// The byte offset of the word in the input
+        offset: usize,
+        /// The length of the word, counted like `max_tweet_length`
+        length: usize,
+        /// The room for text in the tweet it had to fit in
+        budget: usize,
+    },
+    UndefinedVariable {
+        name: String,
+    },
+    InvalidTemplate {
+        details: String,
+    },
+    InvalidOption {
+        details: String,
+    },
+    InvalidInput {
+        details: String,
+    },
+    /// A tweet breaks one of its platform's rules other than length, see `validate`
+    RuleViolation {
+        details: String,
+    },
+}
+
+impl TweetSplitError {
+    /// A short, stable code for the kind of error, which `explain::explain` describes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TweetSplitError::MaxTweetLengthTooShort { .. } => "TS001",
+            TweetSplitError::WordTooLong { .. } => "TS006",
+            TweetSplitError::UndefinedVariable { .. } => "TS002",
+            TweetSplitError::InvalidTemplate { .. } => "TS003",
+            TweetSplitError::InvalidOption { .. } => "TS004",
+            TweetSplitError::InvalidInput { .. } => "TS005",
+            TweetSplitError::RuleViolation { .. } => "TS007",
+        }
+    }
+}
+
+impl std::fmt::Display for TweetSplitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TweetSplitError::MaxTweetLengthTooShort { details } => write!(f, "{}", details),
+            TweetSplitError::WordTooLong {
+                word,
+                length,
+                budget,
+                ..
+            } => write!(
+                f,
+                "`{}` is {} long, more than the {} available in its tweet.",
+                word, length, budget
+            ),
+            TweetSplitError::UndefinedVariable { name } => {
+                write!(f, "Variable `{}` is not defined.", name)
+            }
+            TweetSplitError::InvalidTemplate { details } => write!(f, "{}", details),
+            TweetSplitError::InvalidOption { details } => write!(f, "{}", details),
+            TweetSplitError::InvalidInput { details } => write!(f, "{}", details),
+            TweetSplitError::RuleViolation { details } => write!(f, "{}", details),
+        }
+    }
+}
+
+impl std::error::Error for TweetSplitError {
+    fn description(&self) -> &str {
+        match self {
+            TweetSplitError::MaxTweetLengthTooShort { details } => details,
+            TweetSplitError::WordTooLong { .. } => "word is longer than a tweet",
+            TweetSplitError::UndefinedVariable { .. } => "variable is not defined",
+            TweetSplitError::InvalidTemplate { details } => details,
+            TweetSplitError::InvalidOption { details } => details,
+            TweetSplitError::InvalidInput { details } => details,
+            TweetSplitError::RuleViolation { details } => details,
+        }
+    }
+}
+
+/// Options controlling how text is split into tweets.
+#[derive(Clone, Debug)]
+pub struct SplitOptions {
+    pub max_tweet_length: usize,
+    /// The unit `max_tweet_length` is measured in
+    pub count: CountMode,
+    /// Rendered before the text of every tweet
+    pub prefix: Option<Template>,
+    /// Rendered after the text of every tweet
+    pub suffix: Option<Template>,
+    /// The date used by the `today` template helper
+    pub today: Option<Date>,
+    /// A counter like "1/5" added to every tweet
+    pub numbering: Option<Numbering>,
+    /// A terminator like "/end" added to the final tweet
+    pub end_marker: Option<String>,
+    /// More decorations, like `decorate::Hashtags`, applied before the ones above
+    /// and so closest to the text
+    pub decorators: Vec<Arc<dyn TweetDecorator>>,
+    /// The mentions a platform puts before each reply, outside every other decoration
+    pub reply_mentions: Option<ReplyMentions>,
+    /// Whether blank-line separated paragraphs are reflowed or kept as tweets
+    pub paragraphs: Paragraphs,
+    /// A block at the end of the input kept intact as its own final tweet
+    pub sign_off: Option<SignOff>,
+    /// Patterns whose matches are not split across tweets unless they cannot fit in one,
+    /// like `glue::initials()`
+    pub keep_together: Vec<Regex>,
+    /// What to do with a word longer than a tweet
+    pub overflow: OverflowPolicy,
+    /// Where tweets prefer to end
+    pub strategy: SplitStrategy,
+    /// Whether more of a longer thread follows these tweets, so that none of them
+    /// gets what only the thread's last tweet does, like the end marker.
+    /// `sections::split_thread` sets this for every section but the last
+    pub continues: bool,
+}
+
+/// How to find the sign-off block at the end of the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignOff {
+    /// The last `n` lines
+    Lines(usize),
+    /// Everything from the last line consisting only of this delimiter, like `--`
+    Delimiter(String),
+}
+
+impl SignOff {
+    // the byte offset in `input` where the sign-off block starts, if there is one
+    fn find(&self, input: &str) -> Option<usize> {
+        let mut line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(newline, _)| newline + 1))
+            .collect::<Vec<usize>>();
+        line_starts.reverse();
+
+        match self {
+            SignOff::Lines(0) => None,
+            SignOff::Lines(n) => Some(*line_starts.get(n - 1).unwrap_or(&0)),
+            SignOff::Delimiter(delimiter) => line_starts.into_iter().find(|start| {
+                let line = input[*start..].lines().next().unwrap_or("");
+                line.trim() == delimiter.trim()
+            }),
+        }
+    }
+}
+
+/// How paragraphs in the input map onto tweets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paragraphs {
+    /// Split the text without regard for paragraphs
+    Reflow,
+    /// Keep each paragraph as its own tweet if every paragraph fits,
+    /// otherwise reflow
+    Auto,
+    /// Keep each paragraph as its own tweet, failing if one does not fit
+    Keep,
+}
+
+impl std::str::FromStr for Paragraphs {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reflow" => Ok(Paragraphs::Reflow),
+            "auto" => Ok(Paragraphs::Auto),
+            "keep" => Ok(Paragraphs::Keep),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown paragraph mode `{}`, expected reflow, auto, or keep.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// What to do with a word, like a long URL, that is longer than the room in a tweet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fail with `TweetSplitError::WordTooLong`
+    Error,
+    /// Split the word on grapheme boundaries across as many tweets as it takes
+    HardSplit,
+    /// Put the word in a tweet of its own, longer than the limit
+    Allow,
+    /// Keep as much of the word as fits in its own tweet and drop the rest
+    Truncate,
+}
+
+/// Where tweets prefer to end, when they have room to choose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// After as many words as fit
+    Words,
+    /// At the last sentence end that fits, like `.`, `!`, or `?`,
+    /// as long as the tweet is at least half full, otherwise after as many words as fit
+    Sentences,
+    /// At the last line end that fits, so that lines like those of a poem or a list
+    /// are only split across tweets when a line is longer than a tweet
+    Lines,
+    /// Only at line ends, keeping each line's indentation, failing if a line is longer
+    /// than a tweet, for code and other text that can't be reflowed like prose
+    Preformatted,
+}
+
+impl std::str::FromStr for SplitStrategy {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "words" => Ok(SplitStrategy::Words),
+            "sentences" => Ok(SplitStrategy::Sentences),
+            "lines" => Ok(SplitStrategy::Lines),
+            "preformatted" => Ok(SplitStrategy::Preformatted),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown split strategy `{}`, expected words, sentences, lines, or preformatted.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(OverflowPolicy::Error),
+            "split" => Ok(OverflowPolicy::HardSplit),
+            "allow" => Ok(OverflowPolicy::Allow),
+            "truncate" => Ok(OverflowPolicy::Truncate),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown overflow policy `{}`, expected error, split, allow, or truncate.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl SplitOptions {
+    pub fn new(max_tweet_length: usize) -> Self {
+        Self {
+            max_tweet_length,
+            count: CountMode::Bytes,
+            prefix: None,
+            suffix: None,
+            today: None,
+            numbering: None,
+            end_marker: None,
+            decorators: vec![],
+            reply_mentions: None,
+            paragraphs: Paragraphs::Reflow,
+            sign_off: None,
+            keep_together: vec![],
+            overflow: OverflowPolicy::Error,
+            strategy: SplitStrategy::Words,
+            continues: false,
+        }
+    }
+
+    fn depends_on_total(&self) -> bool {
+        self.decorations()
+            .any(|decoration| decoration.depends_on_total())
+    }
+
+    // every decoration, innermost first
+    fn decorations(&self) -> impl DoubleEndedIterator<Item = Decoration<'_>> {
+        let custom = self
+            .decorators
+            .iter()
+            .map(|decorator| Decoration::Custom(decorator.as_ref()));
+
+        custom
+            .chain(self.prefix.iter().map(Decoration::Prefix))
+            .chain(self.suffix.iter().map(Decoration::Suffix))
+            .chain(self.numbering.iter().map(Decoration::Numbering))
+            .chain(self.end_marker.as_deref().map(Decoration::EndMarker))
+            .chain(
+                self.reply_mentions
+                    .iter()
+                    .map(|mentions| Decoration::Custom(mentions)),
+            )
+    }
+
+    fn position(&self, index: usize, total: usize, is_last: bool) -> TweetPosition {
+        TweetPosition {
+            index,
+            total,
+            is_last: is_last && !self.continues,
+            today: self.today,
+        }
+    }
+
+    // the tweet at 0-based `index` with its decorations
+    fn render(&self, index: usize, total: usize, text: &str) -> Result<String, TweetSplitError> {
+        let position = self.position(index, total, index + 1 == total);
+        let mut tweet = String::with_capacity(self.max_tweet_length.max(text.len()));
+
+        for decoration in self.decorations().rev() {
+            decoration.before(&position, text, &mut tweet)?;
+        }
+        tweet.push_str(text);
+        for decoration in self.decorations() {
+            decoration.after(&position, text, &mut tweet)?;
+        }
+
+        Ok(tweet)
+    }
+
+    // the room left for text in the tweet at 0-based `index`
+    fn budget(&self, index: usize, total: usize, is_last: bool) -> Result<usize, TweetSplitError> {
+        self.budget_within(index, total..=total, is_last)
+    }
+
+    // the room left for text in the tweet at 0-based `index`
+    // in a thread of any of `totals` tweets
+    fn budget_within(
+        &self,
+        index: usize,
+        totals: RangeInclusive<usize>,
+        is_last: bool,
+    ) -> Result<usize, TweetSplitError> {
+        let mut reserved = 0;
+        for decoration in self.decorations() {
+            let totals = if decoration.depends_on_total() {
+                totals.clone()
+            } else {
+                *totals.start()..=*totals.start()
+            };
+
+            let mut widest = 0;
+            for total in totals {
+                let position = self.position(index, total, is_last);
+                widest = widest.max(decoration.reserved_width(&position, self.count)?);
+            }
+            reserved += widest;
+        }
+
+        self.max_tweet_length
+            .checked_sub(reserved)
+            .filter(|budget| *budget > 0)
+            .ok_or_else(|| TweetSplitError::MaxTweetLengthTooShort {
+                details: format!(
+                    "Tweet length of {} is too short to fit the decorations.",
+                    self.max_tweet_length
+                ),
+            })
+    }
+}
+
+/// Where the room in one tweet went, in the units of `max_tweet_length`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BudgetUsage {
+    pub max_tweet_length: usize,
+    /// The tweet's slice of the input
+    pub content: usize,
+    pub prefix: usize,
+    pub suffix: usize,
+    pub numbering: usize,
+    pub end_marker: usize,
+    /// What `SplitOptions::decorators` added
+    pub decorators: usize,
+    /// Room held for decorations that they did not use in this tweet,
+    /// like the extra digit a counter keeps for "10/10" in "9/10"
+    pub reserved: usize,
+    /// Room left over
+    pub unused: usize,
+}
+
+impl SplitOptions {
+    /// How the room in the tweet at 0-based `index` of a `total` tweet thread
+    /// is spent on `text` and each decoration.
+    pub fn budget_usage(
+        &self,
+        index: usize,
+        total: usize,
+        text: &str,
+    ) -> Result<BudgetUsage, TweetSplitError> {
+        let position = self.position(index, total, index + 1 == total);
+        let mut usage = BudgetUsage {
+            max_tweet_length: self.max_tweet_length,
+            content: self.count.count(text),
+            ..BudgetUsage::default()
+        };
+        let mut decorated = String::new();
+        let mut used = usage.content;
+
+        for decoration in self.decorations() {
+            decorated.clear();
+            decoration.before(&position, text, &mut decorated)?;
+            decoration.after(&position, text, &mut decorated)?;
+
+            let width = self.count.count(&decorated);
+            match decoration {
+                Decoration::Custom(_) => usage.decorators += width,
+                Decoration::Prefix(_) => usage.prefix += width,
+                Decoration::Suffix(_) => usage.suffix += width,
+                Decoration::Numbering(_) => usage.numbering += width,
+                Decoration::EndMarker(_) => usage.end_marker += width,
+            }
+            usage.reserved += decoration
+                .reserved_width(&position, self.count)?
+                .saturating_sub(width);
+            used += width;
+        }
+
+        usage.unused = self.max_tweet_length.saturating_sub(used + usage.reserved);
+
+        Ok(usage)
+    }
+}
+
+/// `SplitOptions` built up a setting at a time, for splitting any number of texts.
+///
+/// ```
+/// use tweet_split_core::count::CountMode;
+/// use tweet_split_core::Splitter;
+///
+/// let splitter = Splitter::new(12).count(CountMode::Graphemes).end_marker("🔚");
+///
+/// assert_eq!(
+///     splitter.split("one thread of tweets").unwrap(),
+///     vec!["one thread", "of tweets 🔚"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Splitter {
+    options: SplitOptions,
+}
+
+impl Splitter {
+    pub fn new(max_tweet_length: usize) -> Self {
+        Self::from(SplitOptions::new(max_tweet_length))
+    }
+
+    pub fn count(mut self, count: CountMode) -> Self {
+        self.options.count = count;
+        self
+    }
+
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.options.overflow = overflow;
+        self
+    }
+
+    pub fn prefix(mut self, prefix: Template) -> Self {
+        self.options.prefix = Some(prefix);
+        self
+    }
+
+    pub fn suffix(mut self, suffix: Template) -> Self {
+        self.options.suffix = Some(suffix);
+        self
+    }
+
+    /// Set the prefix and suffix from one template for the whole tweet.
+    pub fn template(mut self, template: TweetTemplate) -> Self {
+        self.options.prefix = Some(template.prefix);
+        self.options.suffix = Some(template.suffix);
+        self
+    }
+
+    pub fn today(mut self, today: Date) -> Self {
+        self.options.today = Some(today);
+        self
+    }
+
+    pub fn numbering(mut self, numbering: Numbering) -> Self {
+        self.options.numbering = Some(numbering);
+        self
+    }
+
+    pub fn end_marker(mut self, end_marker: impl Into<String>) -> Self {
+        self.options.end_marker = Some(end_marker.into());
+        self
+    }
+
+    /// Add a decorator, inside the ones added before it.
+    pub fn decorator(mut self, decorator: impl TweetDecorator + 'static) -> Self {
+        self.options.decorators.push(Arc::new(decorator));
+        self
+    }
+
+    pub fn reply_mentions(mut self, reply_mentions: ReplyMentions) -> Self {
+        self.options.reply_mentions = Some(reply_mentions);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: SplitStrategy) -> Self {
+        self.options.strategy = strategy;
+        self
+    }
+
+    pub fn paragraphs(mut self, paragraphs: Paragraphs) -> Self {
+        self.options.paragraphs = paragraphs;
+        self
+    }
+
+    pub fn sign_off(mut self, sign_off: SignOff) -> Self {
+        self.options.sign_off = Some(sign_off);
+        self
+    }
+
+    /// Add a pattern whose matches are not split across tweets.
+    pub fn keep_together(mut self, pattern: Regex) -> Self {
+        self.options.keep_together.push(pattern);
+        self
+    }
+
+    pub fn options(&self) -> &SplitOptions {
+        &self.options
+    }
+
+    pub fn split(&self, input: &str) -> Result<Vec<String>, TweetSplitError> {
+        split_with_options(input, &self.options)
+    }
+
+    pub fn split_thread(&self, input: &str) -> Result<Thread, TweetSplitError> {
+        split_thread(input, &self.options)
+    }
+
+    pub fn split_tweets(&self, input: &str) -> Result<Vec<Tweet>, TweetSplitError> {
+        split_tweets(input, &self.options)
+    }
+
+    /// Tokenize `input` once for splitting at several limits.
+    pub fn prepare<'a>(&'a self, input: &'a str) -> PreparedText<'a> {
+        PreparedText::new(input, &self.options)
+    }
+}
+
+impl From<SplitOptions> for Splitter {
+    fn from(options: SplitOptions) -> Self {
+        Self { options }
+    }
+}
+
+pub fn split_text(input: &str, max_tweet_length: usize) -> Result<Vec<String>, TweetSplitError> {
+    Splitter::new(max_tweet_length).split(input)
+}
+
+/// Like `split_text`, but returning each tweet as a slice of `input`
+/// rather than a copy, since without decorations that's all a tweet is.
+pub fn split_text_borrowed(
+    input: &str,
+    max_tweet_length: usize,
+) -> Result<Vec<&str>, TweetSplitError> {
+    let options = SplitOptions::new(max_tweet_length);
+    let trimmed = input.trim();
+
+    if fits_in_one_tweet(trimmed, &options) {
+        return Ok(vec![trimmed]);
+    }
+
+    // with no sign-off, the body being split is all of the trimmed input
+    let tweets = PreparedText::new(input, &options).reflow(&options, 0)?;
+
+    Ok(tweets
+        .into_iter()
+        .map(|(start, end)| &trimmed[start..end])
+        .collect())
+}
+
+pub fn split_with_options(
+    input: &str,
+    options: &SplitOptions,
+) -> Result<Vec<String>, TweetSplitError> {
+    split_thread(input, options).map(|thread| thread.tweets)
+}
+
+/// Like `split_with_options`, but keeping each tweet's text alongside its rendering.
+pub fn split_thread(input: &str, options: &SplitOptions) -> Result<Thread, TweetSplitError> {
+    let trimmed = input.trim();
+
+    if fits_in_one_tweet(trimmed, options) {
+        let leading = input.len() - input.trim_start().len();
+        let range = leading..leading + trimmed.len();
+        return Ok(Thread {
+            texts: vec![trimmed.to_string()],
+            tweets: vec![options.render(0, 1, trimmed)?],
+            ranges: vec![range],
+        });
+    }
+
+    PreparedText::new(input, options).split(options.max_tweet_length)
+}
+
+/// Like `split_with_options`, but with each tweet's position, length,
+/// and place in `input`, as `Thread::tweets_counted` gives them.
+pub fn split_tweets(input: &str, options: &SplitOptions) -> Result<Vec<Tweet>, TweetSplitError> {
+    split_thread(input, options).map(|thread| thread.tweets_counted(options.count))
+}
+
+/// Like `split_with_options`, but rendering each tweet only when it is asked for,
+/// so taking the first few tweets of a long text doesn't build the rest.
+///
+/// Tweets are packed as they're asked for too, unless a decoration depends on the
+/// total number of tweets, like a counter, or paragraphs or a sign-off are kept:
+/// then the whole thread is split on the first call. Either way, the input is
+/// tokenized up front. After an error, the iterator ends.
+pub fn split_text_iter<'a>(
+    input: &'a str,
+    options: &'a SplitOptions,
+) -> impl Iterator<Item = Result<String, TweetSplitError>> + 'a {
+    let lazy = !options.depends_on_total()
+        && options.strategy != SplitStrategy::Preformatted
+        && options.sign_off.is_none()
+        && options.paragraphs == Paragraphs::Reflow;
+
+    let tweets: Box<dyn Iterator<Item = Result<String, TweetSplitError>>> = if lazy {
+        Box::new(LazyTweets {
+            text: PreparedText::new(input, options),
+            packing: Packing::default(),
+        })
+    } else {
+        Box::new(
+            std::iter::once_with(move || match split_with_options(input, options) {
+                Ok(tweets) => tweets.into_iter().map(Ok).collect(),
+                Err(error) => vec![Err(error)],
+            })
+            .flatten(),
+        )
+    };
+
+    let mut failed = false;
+    tweets.take_while(move |tweet| !std::mem::replace(&mut failed, tweet.is_err()))
+}
+
+// tweets packed and rendered one at a time,
+// for options where no decoration depends on the total
+struct LazyTweets<'a> {
+    text: PreparedText<'a>,
+    packing: Packing,
+}
+
+impl Iterator for LazyTweets<'_> {
+    type Item = Result<String, TweetSplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let options = self.text.options;
+        let index = self.packing.tweets;
+
+        let packed = self
+            .text
+            .pack_next(options, &mut self.packing, |index, is_last| {
+                options.budget(index, index + 1, is_last)
+            })?;
+
+        // the total is unknown until the last tweet, and no decoration depends on it,
+        // so any total with the right tweet last will do
+        let is_last = self.packing.next_word >= self.text.words.len();
+        let total = if is_last { index + 1 } else { index + 2 };
+
+        Some(
+            packed
+                .and_then(|(start, end)| options.render(index, total, &self.text.body[start..end])),
+        )
+    }
+}
+
+// whether trimmed `input` would come out as a single tweet of all of it,
+// which is most inputs, checked without tokenizing
+fn fits_in_one_tweet(input: &str, options: &SplitOptions) -> bool {
+    let has_sign_off = || {
+        options
+            .sign_off
+            .as_ref()
+            .and_then(|sign_off| sign_off.find(input))
+            .is_some()
+    };
+    let has_paragraphs =
+        || options.paragraphs != Paragraphs::Reflow && PARAGRAPH_SEPARATOR.is_match(input);
+
+    !input.is_empty()
+        && options
+            .budget(0, 1, true)
+            .is_ok_and(|budget| options.count.count(input) <= budget)
+        && !has_sign_off()
+        && !has_paragraphs()
+}
+
+/// Text tokenized once, so that it can be split at several limits
+/// with only the packing repeated, as when suggesting a limit
+/// or dragging a limit slider in an editor.
+#[derive(Clone, Debug)]
+pub struct PreparedText<'a> {
+    // the trimmed input, and how many bytes and lines were trimmed from its start
+    input: &'a str,
+    leading: usize,
+    leading_lines: usize,
+    body: &'a str,
+    sign_off_start: Option<usize>,
+    words: Vec<(usize, usize)>,
+    // the counted length of the body up to the start and end of each word,
+    // so that any run of words can be measured without counting it again
+    counted: Vec<(usize, usize)>,
+    breakable: Vec<bool>,
+    // whether each word ends a sentence or a line, when splitting by sentences or lines
+    strategy_ends: Vec<bool>,
+    options: &'a SplitOptions,
+}
+
+impl<'a> PreparedText<'a> {
+    /// Tokenize `input` for splitting with `options`,
+    /// whose `max_tweet_length` is replaced by the one given to each split.
+    pub fn new(input: &'a str, options: &'a SplitOptions) -> Self {
+        let leading = input.len() - input.trim_start().len();
+        let leading_lines = input[..leading].matches('\n').count();
+        let input = input.trim();
+
+        let sign_off_start = options
+            .sign_off
+            .as_ref()
+            .and_then(|sign_off| sign_off.find(input));
+        let body = match sign_off_start {
+            Some(start) => input[..start].trim_end(),
+            None => input,
+        };
+
+        let words = WORD_MATCHER
+            .find_iter(body)
+            .map(|word| (word.start(), word.end()))
+            .collect::<Vec<(usize, usize)>>();
+
+        // graphemes and weights never join across whitespace, so the body can be
+        // counted a word and the whitespace before it at a time
+        let mut counted = Vec::with_capacity(words.len());
+        let (mut length, mut previous_end) = (0, 0);
+        for (start, end) in &words {
+            length += options.count.count(&body[previous_end..*start]);
+            let word_start = length;
+            length += options.count.count(&body[*start..*end]);
+            counted.push((word_start, length));
+            previous_end = *end;
+        }
+
+        let breakable = glue::breakable(body, &words, &options.keep_together);
+        let strategy_ends = match options.strategy {
+            SplitStrategy::Words | SplitStrategy::Preformatted => vec![],
+            SplitStrategy::Sentences => words
+                .iter()
+                .map(|(start, end)| ends_sentence(&body[*start..*end]))
+                .collect(),
+            SplitStrategy::Lines => words
+                .iter()
+                .zip(words.iter().skip(1))
+                .map(|((_, end), (next_start, _))| body[*end..*next_start].contains('\n'))
+                .chain(std::iter::once(true))
+                .collect(),
+        };
+
+        Self {
+            input,
+            leading,
+            leading_lines,
+            body,
+            sign_off_start,
+            words,
+            counted,
+            breakable,
+            strategy_ends,
+            options,
+        }
+    }
+
+    pub fn split(&self, max_tweet_length: usize) -> Result<Thread, TweetSplitError> {
+        let options = if max_tweet_length == self.options.max_tweet_length {
+            Cow::Borrowed(self.options)
+        } else {
+            Cow::Owned(SplitOptions {
+                max_tweet_length,
+                ..self.options.clone()
+            })
+        };
+        let (input, body) = (self.input, self.body);
+        let trailing = if self.sign_off_start.is_some() { 1 } else { 0 };
+
+        let mut tweets = match options.paragraphs {
+            Paragraphs::Reflow => self.reflow(&options, trailing)?,
+            Paragraphs::Auto => match keep_paragraphs(body, &options, trailing) {
+                Ok(tweets) => tweets,
+                Err(_) => self.reflow(&options, trailing)?,
+            },
+            Paragraphs::Keep => keep_paragraphs(body, &options, trailing)?,
+        };
+
+        if let Some(start) = self.sign_off_start {
+            let index = tweets.len();
+            let budget = options.budget(index, index + 1, true)?;
+            let length = options.count.count(&input[start..]);
+
+            if length > budget {
+                return Err(TweetSplitError::MaxTweetLengthTooShort {
+                    details: format!(
+                        "The sign-off is {} long, more than the {} available in the final tweet.",
+                        length, budget
+                    ),
+                });
+            }
+
+            tweets.push((start, input.len()));
+        }
+
+        let total = tweets.len();
+        let texts = tweets
+            .iter()
+            .map(|(start, end)| input[*start..*end].to_string())
+            .collect::<Vec<String>>();
+        let ranges = tweets
+            .into_iter()
+            .map(|(start, end)| self.leading + start..self.leading + end)
+            .collect();
+
+        let tweets = texts
+            .iter()
+            .enumerate()
+            .map(|(index, text)| options.render(index, total, text))
+            .collect::<Result<Vec<String>, TweetSplitError>>()?;
+
+        Ok(Thread {
+            texts,
+            tweets,
+            ranges,
+        })
+    }
+
+    // the byte range of each tweet when splitting the body only on whitespace,
+    // leaving room in the thread for `trailing` more tweets after these
+    fn reflow(
+        &self,
+        options: &SplitOptions,
+        trailing: usize,
+    ) -> Result<Vec<(usize, usize)>, TweetSplitError> {
+        // decorations like `{{index}}/{{total}}` take up more room as the thread grows,
+        // but roman numerals can take less, so the count of tweets and the room for
+        // their text depend on each other. pack leaving room for the widest decorations
+        // of a range of totals, widening it to every count seen until a count falls
+        // inside it: then every tweet fits the decorations of the real total.
+        // the range only grows, so this settles before it outgrows the words.
+        let mut totals = 1..=1;
+        let mut first_attempt = true;
+
+        loop {
+            let tweets = self.pack_by_strategy(options, |index, is_last| {
+                options.budget_within(index, totals.clone(), is_last && trailing == 0)
+            })?;
+
+            let count = tweets.len() + trailing;
+            if totals.contains(&count) || tweets.is_empty() || !options.depends_on_total() {
+                return Ok(tweets);
+            }
+
+            // packing for a total of 1 is only a first guess at the count
+            totals = if first_attempt {
+                count..=count
+            } else {
+                *totals.start().min(&count)..=*totals.end().max(&count)
+            };
+            first_attempt = false;
+        }
+    }
+}
+
+// the byte range of each paragraph of `input`, as long as all of them fit in a tweet
+fn keep_paragraphs(
+    input: &str,
+    options: &SplitOptions,
+    trailing: usize,
+) -> Result<Vec<(usize, usize)>, TweetSplitError> {
+    let mut paragraphs = vec![];
+    let mut start = 0;
+
+    for separator in PARAGRAPH_SEPARATOR.find_iter(input) {
+        paragraphs.push((start, separator.start()));
+        start = separator.end();
+    }
+    if start < input.len() {
+        paragraphs.push((start, input.len()));
+    }
+
+    let total = paragraphs.len() + trailing;
+
+    for (index, (start, end)) in paragraphs.iter().enumerate() {
+        let budget = options.budget(index, total, index + 1 == total)?;
+        let length = options.count.count(&input[*start..*end]);
+
+        if length > budget {
+            return Err(TweetSplitError::MaxTweetLengthTooShort {
+                details: format!(
+                    "Paragraph {} is {} long, more than the {} available in its tweet.",
+                    index + 1,
+                    length,
+                    budget
+                ),
+            });
+        }
+    }
+
+    Ok(paragraphs)
+}
+
+// how far packing has got through the words of a `PreparedText`
+#[derive(Clone, Copy, Debug, Default)]
+struct Packing {
+    tweets: usize,
+    next_word: usize,
+    // where the rest of word `next_word` starts and its counted length,
+    // once the start of it has been split off
+    rest: Option<(usize, usize)>,
+}
+
+impl PreparedText<'_> {
+    // the byte range of every tweet, packed by `pack_next`
+    fn pack<F>(
+        &self,
+        options: &SplitOptions,
+        budget: F,
+    ) -> Result<Vec<(usize, usize)>, TweetSplitError>
+    where
+        F: Fn(usize, bool) -> Result<usize, TweetSplitError>,
+    {
+        let mut packing = Packing::default();
+
+        std::iter::from_fn(|| self.pack_next(options, &mut packing, &budget)).collect()
+    }
+
+    // the byte range of every tweet, packed as `options.strategy` packs them
+    fn pack_by_strategy<F>(
+        &self,
+        options: &SplitOptions,
+        budget: F,
+    ) -> Result<Vec<(usize, usize)>, TweetSplitError>
+    where
+        F: Fn(usize, bool) -> Result<usize, TweetSplitError>,
+    {
+        if options.strategy == SplitStrategy::Preformatted {
+            self.pack_lines(options, budget)
+        } else {
+            self.pack(options, budget)
+        }
+    }
+
+    // the byte range of every tweet, grouping whole lines greedily,
+    // like `pack_next` does words. each tweet starts at the start of a line,
+    // so it keeps the line's indentation, and blank lines between tweets are discarded
+    fn pack_lines<F>(
+        &self,
+        options: &SplitOptions,
+        budget: F,
+    ) -> Result<Vec<(usize, usize)>, TweetSplitError>
+    where
+        F: Fn(usize, bool) -> Result<usize, TweetSplitError>,
+    {
+        let body = self.body;
+        let mut lines = vec![];
+        let mut line_start = 0;
+        for line in body.split_inclusive('\n') {
+            let content = line.trim_end();
+            if !content.is_empty() {
+                lines.push((line_start, line_start + content.len()));
+            }
+            line_start += line.len();
+        }
+
+        let line_number =
+            |start: usize| self.leading_lines + body[..start].matches('\n').count() + 1;
+        let mut tweets: Vec<(usize, usize)> = vec![];
+        let mut next = 0;
+
+        while next < lines.len() {
+            let index = tweets.len();
+            let (start, _) = lines[next];
+            let (_, final_end) = lines[lines.len() - 1];
+
+            let last_budget = budget(index, true)?;
+            if options.count.count(&body[start..final_end]) <= last_budget {
+                tweets.push((start, final_end));
+                break;
+            }
+
+            // a tweet that isn't last must leave at least one line for the last
+            let budget = if next + 1 == lines.len() {
+                last_budget
+            } else {
+                budget(index, false)?
+            };
+            let mut length = 0;
+            let mut end = start;
+            while next < lines.len() {
+                let (_, line_end) = lines[next];
+                let added = options.count.count(&body[end..line_end]);
+                if length + added > budget {
+                    break;
+                }
+                length += added;
+                end = line_end;
+                next += 1;
+            }
+
+            if end == start {
+                let (start, end) = lines[next];
+                return Err(TweetSplitError::MaxTweetLengthTooShort {
+                    details: format!(
+                        "Line {} is {} long, more than the {} available in its tweet.",
+                        line_number(start),
+                        options.count.count(&body[start..end]),
+                        budget
+                    ),
+                });
+            }
+            tweets.push((start, end));
+        }
+
+        Ok(tweets)
+    }
+
+    // greedily group words into the next tweet, returning its byte range in the body,
+    // or `None` once every word is in a tweet.
+    // whitespace between words in the same tweet is kept as-is,
+    // whitespace between tweets is discarded.
+    // `budget` is given the 0-based index of the tweet and whether it is the last one,
+    // since the last tweet can have different decorations than the rest.
+    // tweets end after a word marked `breakable` when possible,
+    // and anywhere they have to otherwise.
+    // a word longer than its tweet is handled by `options.overflow`.
+    fn pack_next<F>(
+        &self,
+        options: &SplitOptions,
+        packing: &mut Packing,
+        budget: F,
+    ) -> Option<Result<(usize, usize), TweetSplitError>>
+    where
+        F: Fn(usize, bool) -> Result<usize, TweetSplitError>,
+    {
+        let (input, words, counted) = (self.body, &self.words, &self.counted);
+        let next_word = packing.next_word;
+        if next_word >= words.len() {
+            return None;
+        }
+
+        let index = packing.tweets;
+        packing.tweets += 1;
+        let (start, first_word_end) = words[next_word];
+        let (start, first_length) = packing
+            .rest
+            .take()
+            .unwrap_or((start, counted[next_word].1 - counted[next_word].0));
+        let last_word = words.len() - 1;
+        let (_, final_word_end) = words[last_word];
+
+        // the counted length from `start` to the end of word `last`
+        let span = |last: usize| first_length + counted[last].1 - counted[next_word].1;
+
+        // everything that is left fits in a final tweet
+        let last_budget = match budget(index, true) {
+            Ok(budget) => budget,
+            Err(error) => return Some(Err(error)),
+        };
+        if span(last_word) <= last_budget {
+            packing.next_word = words.len();
+            return Some(Ok((start, final_word_end)));
+        }
+
+        // otherwise this tweet must leave at least one word for a final tweet
+        let budget = match budget(index, false) {
+            Ok(budget) => budget,
+            Err(error) => return Some(Err(error)),
+        };
+
+        // only the last word is left, and it doesn't fit the final tweet
+        let overflowing = if next_word == last_word {
+            Some(last_budget)
+        } else {
+            Some(budget).filter(|budget| first_length > *budget)
+        };
+
+        if let Some(budget) = overflowing {
+            let word = &input[start..first_word_end];
+            let fits = fitting_prefix(word, budget, options.count);
+            packing.next_word += 1;
+
+            return Some(match options.overflow {
+                OverflowPolicy::Allow => Ok((start, first_word_end)),
+                OverflowPolicy::Truncate if fits > 0 => Ok((start, start + fits)),
+                OverflowPolicy::HardSplit if fits > 0 => {
+                    packing.next_word = next_word;
+                    packing.rest = Some((start + fits, options.count.count(&word[fits..])));
+                    Ok((start, start + fits))
+                }
+                _ => Err(TweetSplitError::WordTooLong {
+                    word: word.to_string(),
+                    offset: self.leading + start,
+                    length: first_length,
+                    budget,
+                }),
+            });
+        }
+
+        // a sentence end is only worth stopping at in a tweet that is at least half full,
+        // a line end always is
+        let sentence_end = |word: usize| {
+            self.strategy_ends.get(word) == Some(&true)
+                && (options.strategy == SplitStrategy::Lines || span(word) * 2 >= budget)
+        };
+
+        let mut end = first_word_end;
+        let mut next = next_word + 1;
+        let mut preferred = Some((end, next)).filter(|_| self.breakable[next_word]);
+        let mut sentence = preferred.filter(|_| sentence_end(next_word));
+
+        while next < last_word {
+            let (_, word_end) = words[next];
+
+            if span(next) > budget {
+                break;
+            }
+
+            end = word_end;
+            next += 1;
+
+            if self.breakable[next - 1] {
+                preferred = Some((end, next));
+                if sentence_end(next - 1) {
+                    sentence = preferred;
+                }
+            }
+        }
+
+        let (end, next) = sentence.or(preferred).unwrap_or((end, next));
+        packing.next_word = next;
+
+        Some(Ok((start, end)))
+    }
+}
+
+// whether `word` ends with sentence-ending punctuation, before any closing quotes or brackets
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', '”', '’', ')', ']', '»'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+// the byte length of the most whole graphemes from the start of `word` that fit in `budget`,
+// each prefix measured whole, as its tweet will be, since a prefix that has become a link
+// counts as one. prefixes whose graphemes add up to more than `budget` are never tried:
+// only a link counts for less than its graphemes, and a shorter prefix is still a fit
+fn fitting_prefix(word: &str, budget: usize, count: CountMode) -> usize {
+    let mut used = 0;
+
+    word.grapheme_indices(true)
+        .take_while(|(_, grapheme)| {
+            used += count.count(grapheme);
+            used <= budget
+        })
+        .map(|(offset, grapheme)| offset + grapheme.len())
+        .filter(|end| count.count(&word[..*end]) <= budget)
+        .last()
+        .unwrap_or(0)
+}
+
+lazy_static! {
+    static ref WORD_MATCHER: Regex = Regex::new(r"\S+").unwrap();
+    static ref PARAGRAPH_SEPARATOR: Regex = Regex::new(r"\s*\n[ \t]*\n\s*").unwrap();
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_borrow)]
+mod tests {
+    use super::*;
+    use numbering::NumberingPosition;
+
+    const TRAITOROUS_EIGHT: &str = "The traitorous eight was a group of eight employees who left Shockley Semiconductor Laboratory in 1957 to found Fairchild Semiconductor. William Shockley had in 1956 recruited a group of young PhD graduates with the goal to develop and produce new semiconductor devices. While Shockley had received a Nobel Prize in Physics and was an experienced researcher and teacher, his management of the group was authoritarian and unpopular. This was accentuated by Shockley's research focus not proving fruitful. After the demand for Shockley to be replaced was rebuffed, the eight left to form their own company.";
+
+    #[test]
+    fn it_splits() {
+        let input = "aaaaaaaaa bbbbbbbbb ccccccccc ddddddddd eeeeeeeee ";
+
+        let splits = split_text(&input, 10).unwrap();
+
+        assert_eq!(splits.len(), 5);
+    }
+
+    #[test]
+    fn it_trims_spaces_at_splits() {
+        let input = "aaaaaaaaa bbbbbbbbb ccccccccc ddddddddd eeeeeeeee ";
+
+        let splits = split_text(&input, 10).unwrap();
+
+        for split in splits {
+            assert_eq!(split.len(), 9);
+        }
+    }
+
+    #[test]
+    fn it_properly_splits_at_smaller_char_sizes() {
+        let input = TRAITOROUS_EIGHT;
+
+        let splits = split_text(input, 25).unwrap();
+
+        assert_eq!(splits[0], "The traitorous eight was");
+    }
+
+    #[test]
+    fn it_trims_trailing_spaces() {
+        let input = TRAITOROUS_EIGHT;
+
+        for max_tweet_length in 14..=250 {
+            let splits = split_text(input, max_tweet_length).unwrap();
+
+            for split in splits {
+                assert_ne!(split.chars().collect::<Vec<char>>().last().unwrap(), &' ');
+            }
+        }
+    }
+
+    #[test]
+    fn it_keeps_a_single_word_that_fits() {
+        assert_eq!(split_text("  hello  ", 10).unwrap(), vec!["hello"]);
+    }
+
+    #[test]
+    fn it_reserves_room_for_prefixes_and_suffixes() {
+        let mut options = SplitOptions::new(25);
+        options.prefix = Some(Template::parse("{{index}}/{{total}} ").unwrap());
+        options.suffix = Some(Template::parse(" 🧵").unwrap());
+
+        let splits = split_with_options(TRAITOROUS_EIGHT, &options).unwrap();
+
+        assert_eq!(splits.len(), 47);
+        assert_eq!(splits[0], "1/47 The traitorous 🧵");
+        for split in &splits {
+            assert!(split.len() <= 25, "{:?} is too long", split);
+        }
+        assert!(splits[46].starts_with("47/47 "));
+    }
+
+    #[test]
+    fn it_repacks_when_the_total_grows_a_digit() {
+        let input = "aaaa ".repeat(10);
+        let mut options = SplitOptions::new(10);
+        options.prefix = Some(Template::parse("{{index}}/{{total}} ").unwrap());
+
+        let splits = split_with_options(&input, &options).unwrap();
+
+        assert_eq!(splits.len(), 10);
+        assert_eq!(splits[9], "10/10 aaaa");
+        for split in &splits {
+            assert!(split.len() <= 10, "{:?} is too long", split);
+        }
+    }
+
+    #[test]
+    fn it_numbers_tweets() {
+        let input = "aaaaaaaaa bbbbbbbbb ccccccccc ddddddddd eeeeeeeee ";
+        let mut options = SplitOptions::new(15);
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            splits,
+            vec![
+                "aaaaaaaaa 1/5",
+                "bbbbbbbbb 2/5",
+                "ccccccccc 3/5",
+                "ddddddddd 4/5",
+                "eeeeeeeee 5/5"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_numbers_only_the_selected_positions() {
+        let input = "aaaaaaaaa bbbbbbbbb ccccccccc ddddddddd eeeeeeeee ";
+        let mut numbering = Numbering::new(
+            numbering::NumberingStyle::Bracketed,
+            NumberingPosition::Prefix,
+        );
+        numbering.positions = "first,last".parse().unwrap();
+        let mut options = SplitOptions::new(15);
+        options.numbering = Some(numbering);
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            splits,
+            vec![
+                "[1/5] aaaaaaaaa",
+                "bbbbbbbbb",
+                "ccccccccc",
+                "ddddddddd",
+                "[5/5] eeeeeeeee"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reserves_room_for_the_end_marker() {
+        let input = "aaaa bbbb cccc dddd";
+        let mut options = SplitOptions::new(9);
+        options.end_marker = Some("/end".to_string());
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(splits, vec!["aaaa bbbb", "cccc", "dddd /end"]);
+    }
+
+    #[test]
+    fn it_returns_no_tweets_for_blank_input() {
+        let mut options = SplitOptions::new(10);
+        options.end_marker = Some("/end".to_string());
+
+        assert!(split_with_options(" \n ", &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_returns_input_that_fits_as_one_tweet() {
+        let mut options = SplitOptions::new(40);
+        options.end_marker = Some("/end".to_string());
+
+        assert_eq!(
+            split_with_options("\n  short and\n\nsweet  \n", &options).unwrap(),
+            vec!["short and\n\nsweet /end"]
+        );
+
+        options.paragraphs = Paragraphs::Keep;
+        assert_eq!(
+            split_with_options("short and\n\nsweet", &options).unwrap(),
+            vec!["short and", "sweet /end"]
+        );
+    }
+
+    #[test]
+    fn it_keeps_paragraphs_that_fit() {
+        let input = "a hand-crafted\nfirst tweet\n\n  second tweet  \n \n\nthird";
+        let mut options = SplitOptions::new(30);
+        options.paragraphs = Paragraphs::Auto;
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            splits,
+            vec![
+                "a hand-crafted\nfirst tweet 1/3",
+                "second tweet 2/3",
+                "third 3/3"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reflows_or_fails_when_a_paragraph_is_too_long() {
+        let input = "short\n\nthis paragraph is too long";
+        let mut options = SplitOptions::new(10);
+
+        options.paragraphs = Paragraphs::Auto;
+        assert_eq!(
+            split_with_options(input, &options).unwrap(),
+            vec!["short", "this", "paragraph", "is too", "long"]
+        );
+
+        options.paragraphs = Paragraphs::Keep;
+        assert!(split_with_options(input, &options).is_err());
+    }
+
+    #[test]
+    fn it_keeps_the_sign_off_in_its_own_tweet() {
+        let input = "aaaa bbbb cccc dddd\n--\nJane · jane.dev";
+        let mut options = SplitOptions::new(24);
+        options.sign_off = Some(SignOff::Delimiter("--".to_string()));
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            splits,
+            vec!["aaaa bbbb cccc dddd 1/2", "--\nJane · jane.dev 2/2"]
+        );
+
+        options.max_tweet_length = 20;
+        options.sign_off = Some(SignOff::Lines(1));
+        options.numbering = None;
+
+        let splits = split_with_options(input, &options).unwrap();
+
+        assert_eq!(splits, vec!["aaaa bbbb cccc dddd", "--", "Jane · jane.dev"]);
+    }
+
+    #[test]
+    fn it_keeps_initials_together() {
+        let input = "The Hobbit by J. R. R. Tolkien";
+        let mut options = SplitOptions::new(20);
+
+        assert_eq!(
+            split_with_options(input, &options).unwrap(),
+            vec!["The Hobbit by J. R.", "R. Tolkien"]
+        );
+
+        options.keep_together = vec![glue::initials()];
+
+        assert_eq!(
+            split_with_options(input, &options).unwrap(),
+            vec!["The Hobbit by", "J. R. R. Tolkien"]
+        );
+
+        // a span that cannot fit in any tweet starts a new one and is split anyway
+        options.max_tweet_length = 10;
+
+        assert_eq!(
+            split_with_options(input, &options).unwrap(),
+            vec!["The Hobbit", "by", "J. R. R.", "Tolkien"]
+        );
+    }
+
+    #[test]
+    fn it_splits_prepared_text_at_many_limits() {
+        let mut options = SplitOptions::new(0);
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        options.sign_off = Some(SignOff::Lines(1));
+        let input = format!("{}\n— Wikipedia", TRAITOROUS_EIGHT);
+
+        let prepared = PreparedText::new(&input, &options);
+
+        for max_tweet_length in 20..=300 {
+            let options = SplitOptions {
+                max_tweet_length,
+                ..options.clone()
+            };
+
+            assert_eq!(
+                prepared.split(max_tweet_length).ok(),
+                split_thread(&input, &options).ok()
+            );
+        }
+    }
+
+    #[test]
+    fn it_reports_words_that_are_too_long() {
+        let input = "\n\n  short words then incomprehensibilities";
+        let mut options = SplitOptions::new(16);
+        options.end_marker = Some("/end".to_string());
+
+        match split_with_options(input, &options) {
+            Err(TweetSplitError::WordTooLong {
+                word,
+                offset,
+                length,
+                budget,
+            }) => {
+                assert_eq!(word, "incomprehensibilities");
+                assert_eq!(length, 21);
+                assert_eq!(&input[offset..offset + word.len()], word);
+                assert_eq!(budget, 11);
+            }
+            other => panic!("expected WordTooLong, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_splits_with_a_built_splitter() {
+        let splitter = Splitter::new(25)
+            .numbering(Numbering::new(
+                numbering::NumberingStyle::Slash,
+                NumberingPosition::Prefix,
+            ))
+            .decorator(decorate::Ellipsis::default())
+            .keep_together(glue::initials());
+
+        let mut options = SplitOptions::new(25);
+        options.numbering = splitter.options().numbering;
+        options.decorators = splitter.options().decorators.clone();
+        options.keep_together = vec![glue::initials()];
+
+        for input in [TRAITOROUS_EIGHT, "The Hobbit by J. R. R. Tolkien"] {
+            assert_eq!(
+                splitter.split(input).unwrap(),
+                split_with_options(input, &options).unwrap()
+            );
+        }
+        assert_eq!(
+            splitter.prepare(TRAITOROUS_EIGHT).split(25).unwrap(),
+            splitter.split_thread(TRAITOROUS_EIGHT).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_fits_tweet_templates_in_the_limit() {
+        let template = TweetTemplate::parse("{index}/{total} {text} 🧵").unwrap();
+        let tweets = Splitter::new(40)
+            .template(template)
+            .split(TRAITOROUS_EIGHT)
+            .unwrap();
+
+        let total = tweets.len();
+        assert!(tweets[0].starts_with(&format!("1/{} The traitorous", total)));
+        for tweet in &tweets {
+            assert!(tweet.ends_with(" 🧵"));
+            assert!(tweet.len() <= 40, "{:?} is too long", tweet);
+        }
+    }
+
+    #[test]
+    fn it_splits_lazily_like_it_splits_eagerly() {
+        let mut ellipsis = SplitOptions::new(40);
+        ellipsis.decorators = vec![Arc::new(decorate::Ellipsis::default())];
+        ellipsis.end_marker = Some("/end".to_string());
+        let all = [
+            SplitOptions::new(30),
+            ellipsis,
+            numbered(40, numbering::NumberingStyle::Slash),
+        ];
+
+        for options in all.iter() {
+            assert_eq!(
+                split_text_iter(TRAITOROUS_EIGHT, options)
+                    .collect::<Result<Vec<String>, TweetSplitError>>()
+                    .unwrap(),
+                split_with_options(TRAITOROUS_EIGHT, options).unwrap()
+            );
+        }
+        assert_eq!(split_text_iter("  ", &all[0]).count(), 0);
+    }
+
+    #[test]
+    fn it_stops_splitting_lazily_at_an_error() {
+        let options = SplitOptions::new(10);
+        let mut tweets = split_text_iter("fine then incomprehensibilities and more", &options);
+
+        assert_eq!(tweets.next().unwrap().unwrap(), "fine then");
+        assert!(matches!(
+            tweets.next(),
+            Some(Err(TweetSplitError::WordTooLong { .. }))
+        ));
+        assert!(tweets.next().is_none());
+    }
+
+    #[test]
+    fn it_splits_into_slices_of_the_input() {
+        let input = format!("  {}\n", TRAITOROUS_EIGHT);
+        let range = input.as_bytes().as_ptr_range();
+
+        for max_tweet_length in [30, 80, 1000] {
+            let tweets = split_text_borrowed(&input, max_tweet_length).unwrap();
+
+            assert_eq!(tweets, split_text(&input, max_tweet_length).unwrap());
+            assert!(tweets.iter().all(|tweet| range.contains(&tweet.as_ptr())));
+        }
+        assert!(split_text_borrowed("incomprehensibilities", 10).is_err());
+    }
+
+    #[test]
+    fn it_prefers_ending_tweets_at_sentences() {
+        let mut options = SplitOptions::new(120);
+
+        let by_words = split_with_options(TRAITOROUS_EIGHT, &options).unwrap();
+        options.strategy = SplitStrategy::Sentences;
+        let by_sentences = split_with_options(TRAITOROUS_EIGHT, &options).unwrap();
+
+        assert_eq!(
+            &by_sentences[3..],
+            [
+                "researcher and teacher, his management of the group was authoritarian and unpopular.",
+                "This was accentuated by Shockley's research focus not proving fruitful.",
+                "After the demand for Shockley to be replaced was rebuffed, the eight left to form their own company.",
+            ]
+        );
+        assert!(!by_words[3].ends_with('.'));
+        // "Fairchild Semiconductor." would leave the second tweet less than half full
+        assert!(by_sentences[1].starts_with("Fairchild Semiconductor. William"));
+    }
+
+    #[test]
+    fn it_prefers_ending_tweets_at_lines() {
+        let poem = "Roses are red,\nviolets are blue,\nsugar is sweet,\nand so are you.";
+        let mut options = SplitOptions::new(40);
+
+        let by_words = split_with_options(poem, &options).unwrap();
+        options.strategy = SplitStrategy::Lines;
+        let by_lines = split_with_options(poem, &options).unwrap();
+
+        assert_eq!(by_words[0], "Roses are red,\nviolets are blue,\nsugar");
+        assert_eq!(
+            by_lines,
+            vec![
+                "Roses are red,\nviolets are blue,",
+                "sugar is sweet,\nand so are you."
+            ]
+        );
+    }
+
+    #[test]
+    fn it_splits_preformatted_text_only_between_lines() {
+        let code = "fn main() {\n    let a = 1;\n\n    let b = 2;\n    assert_eq!(a + b, 3, \"{}\", a);\n}";
+        let mut options = SplitOptions::new(36);
+        options.strategy = SplitStrategy::Preformatted;
+
+        assert_eq!(
+            split_with_options(code, &options).unwrap(),
+            vec![
+                "fn main() {\n    let a = 1;",
+                "    let b = 2;",
+                "    assert_eq!(a + b, 3, \"{}\", a);\n}",
+            ]
+        );
+
+        options.max_tweet_length = 20;
+        match split_with_options(&format!("\n\n{}", code), &options) {
+            Err(TweetSplitError::MaxTweetLengthTooShort { details }) => assert_eq!(
+                details,
+                "Line 7 is 34 long, more than the 20 available in its tweet."
+            ),
+            other => panic!("expected MaxTweetLengthTooShort, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_follows_the_overflow_policy_for_words_that_are_too_long() {
+        let long = "x".repeat(25);
+        let input = format!("see {} ok", long);
+        let split = |overflow| {
+            let mut options = SplitOptions::new(10);
+            options.overflow = overflow;
+            split_with_options(&input, &options)
+        };
+
+        assert!(split(OverflowPolicy::Error).is_err());
+        assert_eq!(
+            split(OverflowPolicy::HardSplit).unwrap(),
+            vec!["see", &long[..10], &long[..10], "xxxxx ok"]
+        );
+        assert_eq!(
+            split(OverflowPolicy::Allow).unwrap(),
+            vec!["see", &long, "ok"]
+        );
+        assert_eq!(
+            split(OverflowPolicy::Truncate).unwrap(),
+            vec!["see", &long[..10], "ok"]
+        );
+    }
+
+    #[test]
+    fn it_hard_splits_on_grapheme_boundaries() {
+        let mut options = SplitOptions::new(5);
+        options.overflow = OverflowPolicy::HardSplit;
+
+        // each é is two bytes, and the family is more than the limit on its own
+        assert_eq!(
+            split_with_options("ééééé", &options).unwrap(),
+            vec!["éé", "éé", "é"]
+        );
+        assert!(split_with_options("👩‍👩‍👧‍👦 family", &options).is_err());
+    }
+
+    #[test]
+    fn it_hard_splits_links_by_how_the_pieces_count() {
+        let link = format!("https://example.com/{}", "a".repeat(40));
+        for count in [CountMode::TwitterWeighted, CountMode::Mastodon] {
+            let mut options = SplitOptions::new(20);
+            options.overflow = OverflowPolicy::HardSplit;
+            options.count = count;
+
+            let tweets = split_with_options(&link, &options).unwrap();
+
+            assert_eq!(tweets.concat(), link);
+            for tweet in &tweets {
+                assert!(count.count(tweet) <= 20, "{:?} is too long", tweet);
+            }
+        }
+    }
+
+    #[test]
+    fn it_applies_decorators_closest_to_the_text() {
+        let mut options = SplitOptions::new(40);
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        options.end_marker = Some("/end".to_string());
+        options
+            .decorators
+            .push(Arc::new(decorate::Ellipsis::default()));
+        options.decorators.push(Arc::new(decorate::Hashtags::new(
+            &["rust"],
+            numbering::Positions::all(),
+        )));
+
+        let tweets = split_with_options(TRAITOROUS_EIGHT, &options).unwrap();
+
+        let total = tweets.len();
+        assert!(tweets[0].ends_with(&format!("… #rust 1/{}", total)));
+        assert!(tweets[total - 1].ends_with(&format!("company. #rust {0}/{0} /end", total)));
+        for tweet in &tweets {
+            assert!(tweet.len() <= 40, "{:?} is too long", tweet);
+        }
+    }
+
+    #[test]
+    fn it_puts_reply_mentions_outside_every_decoration() {
+        let mut options = SplitOptions::new(40);
+        options.numbering = Some(Numbering::new(
+            numbering::NumberingStyle::Slash,
+            NumberingPosition::Prefix,
+        ));
+        options.decorators.push(Arc::new(decorate::Ellipsis {
+            leading: true,
+            ..decorate::Ellipsis::default()
+        }));
+        options.reply_mentions = Some(decorate::ReplyMentions::new(&["bob"], true));
+
+        let tweets = split_with_options(TRAITOROUS_EIGHT, &options).unwrap();
+
+        let total = tweets.len();
+        assert!(tweets[0].starts_with(&format!("1/{} ", total)));
+        assert!(tweets[1].starts_with(&format!("@bob 2/{} …", total)));
+        for tweet in &tweets {
+            assert!(tweet.len() <= 40, "{:?} is too long", tweet);
+        }
+    }
+
+    fn numbered(max_tweet_length: usize, style: numbering::NumberingStyle) -> SplitOptions {
+        let mut options = SplitOptions::new(max_tweet_length);
+        options.numbering = Some(Numbering::new(style, NumberingPosition::Suffix));
+        options
+    }
+
+    // `count` three-letter words
+    fn words(count: usize) -> String {
+        vec!["abc"; count].join(" ")
+    }
+
+    fn assert_settled(tweets: &[String], options: &SplitOptions) {
+        let total = tweets.len();
+        let counter = options.numbering.unwrap().style.render(total, total);
+
+        assert!(tweets[total - 1].ends_with(&format!(" {}", counter)));
+        for tweet in tweets {
+            assert!(
+                tweet.len() <= options.max_tweet_length,
+                "{:?} is too long",
+                tweet
+            );
+        }
+    }
+
+    #[test]
+    fn it_settles_when_the_counter_gains_a_digit() {
+        // " 1/9" leaves room for four words a tweet, " 10/10" only three
+        let options = numbered(20, numbering::NumberingStyle::Slash);
+
+        let nine = split_with_options(&words(36), &options).unwrap();
+        assert_eq!(nine.len(), 9);
+        assert_settled(&nine, &options);
+
+        let ten = split_with_options(&words(37), &options).unwrap();
+        assert_eq!(ten.len(), 13);
+        assert_settled(&ten, &options);
+    }
+
+    #[test]
+    fn it_settles_when_the_counter_gains_a_third_digit() {
+        let options = numbered(22, numbering::NumberingStyle::Slash);
+
+        let ninety_nine = split_with_options(&words(396), &options).unwrap();
+        assert_eq!(ninety_nine.len(), 99);
+        assert_settled(&ninety_nine, &options);
+
+        let hundred = split_with_options(&words(397), &options).unwrap();
+        assert_eq!(hundred.len(), 133);
+        assert_settled(&hundred, &options);
+    }
+
+    #[test]
+    fn it_settles_when_a_longer_thread_has_a_shorter_counter() {
+        // "VIII/VIII" leaves room for three words a tweet and "XI/XI" for four,
+        // so packing for 8 tweets makes 11 and packing for 11 makes 8
+        let options = numbered(23, numbering::NumberingStyle::Roman);
+
+        let tweets = split_with_options(&words(32), &options).unwrap();
+
+        assert_eq!(tweets.len(), 11);
+        assert_settled(&tweets, &options);
+    }
+
+    #[test]
+    fn it_accounts_for_the_budget_of_each_tweet() {
+        let mut options = numbered(30, numbering::NumberingStyle::Slash);
+        options.prefix = Some(Template::parse("> ").unwrap());
+        options.end_marker = Some("/end".to_string());
+
+        let thread = split_thread(&words(12), &options).unwrap();
+        let total = thread.tweets.len();
+        assert_eq!(total, 3);
+
+        let first = options.budget_usage(0, total, &thread.texts[0]).unwrap();
+        assert_eq!(
+            first,
+            BudgetUsage {
+                max_tweet_length: 30,
+                content: 23,
+                prefix: 2,
+                numbering: 4,
+                unused: 1,
+                ..BudgetUsage::default()
+            }
+        );
+
+        let last = options
+            .budget_usage(total - 1, total, &thread.texts[total - 1])
+            .unwrap();
+        assert_eq!(last.end_marker, 5);
+        assert_eq!(
+            last.content + last.prefix + last.numbering + last.end_marker + last.unused,
+            30
+        );
+    }
+
+    #[test]
+    fn it_counts_graphemes() {
+        let input = "👩‍👩‍👧‍👦 café 日本語 👍🏽👍🏽 naïve résumé";
+        let mut options = SplitOptions::new(12);
+        options.count = CountMode::Graphemes;
+
+        let tweets = split_with_options(input, &options).unwrap();
+
+        assert_eq!(tweets, vec!["👩‍👩‍👧‍👦 café 日本語", "👍🏽👍🏽 naïve", "résumé"]);
+        // the family alone is 25 bytes
+        assert!(split_text(input, 12).is_err());
+    }
+
+    #[test]
+    fn it_counts_decorations_in_graphemes() {
+        let mut options = numbered(10, numbering::NumberingStyle::Thread);
+        options.count = CountMode::Graphemes;
+
+        let thread = split_thread(&words(2), &options).unwrap();
+
+        // " 🧵1/2" is 5 graphemes but 8 bytes
+        assert_eq!(thread.tweets, vec!["abc 🧵1/2", "abc 🧵2/2"]);
+        assert_eq!(
+            options
+                .budget_usage(0, 2, &thread.texts[0])
+                .unwrap()
+                .numbering,
+            5
+        );
+    }
+
+    #[test]
+    fn it_counts_links_as_shortened_on_twitter() {
+        let link = format!("https://example.com/{}", "a".repeat(60));
+        let input = format!("read {} today", link);
+        let mut options = SplitOptions::new(40);
+        options.count = CountMode::TwitterWeighted;
+
+        let tweets = split_with_options(&input, &options).unwrap();
+
+        // 5 + 23 + 6, though the link alone is 80 characters
+        assert_eq!(tweets, vec![input.clone()]);
+        assert!(split_text(&input, 40).is_err());
+    }
+}
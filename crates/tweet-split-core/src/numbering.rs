@@ -0,0 +1,481 @@
+// thread counters like "1/5" added to each tweet
+
+use crate::count::{CountMode, Counter};
+use crate::template::decimal_len;
+use crate::TweetSplitError;
+use lazy_static::*;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// How a tweet's position in the thread is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberingStyle {
+    /// `1/5`
+    Slash,
+    /// `1 of 5`
+    Of,
+    /// `[1/5]`
+    Bracketed,
+    /// `🧵1/5`
+    Thread,
+    /// `IV/XII`
+    Roman,
+    /// `D/L`, continuing with `AA`, `AB`, ... past `Z`
+    Letters,
+}
+
+/// Which side of the tweet the counter goes on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberingPosition {
+    Prefix,
+    Suffix,
+}
+
+/// Which tweets of a thread something applies to.
+///
+/// A thread of a single tweet counts as both first and last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Positions {
+    pub first: bool,
+    pub middle: bool,
+    pub last: bool,
+}
+
+impl Positions {
+    pub fn all() -> Self {
+        Self {
+            first: true,
+            middle: true,
+            last: true,
+        }
+    }
+
+    /// Whether the tweet at 0-based `index` is included.
+    pub fn includes(&self, index: usize, is_last: bool) -> bool {
+        let is_first = index == 0;
+
+        (is_first && self.first) || (is_last && self.last) || (!is_first && !is_last && self.middle)
+    }
+}
+
+impl FromStr for Positions {
+    type Err = TweetSplitError;
+
+    /// Parse a comma-separated list like `first,last`,
+    /// or `all` for every tweet.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Positions::all());
+        }
+
+        let mut positions = Positions {
+            first: false,
+            middle: false,
+            last: false,
+        };
+
+        for position in s.split(',') {
+            match position.trim() {
+                "first" => positions.first = true,
+                "middle" => positions.middle = true,
+                "last" => positions.last = true,
+                other => {
+                    return Err(TweetSplitError::InvalidOption {
+                        details: format!(
+                            "Unknown tweet position `{}`, expected first, middle, last, or all.",
+                            other
+                        ),
+                    })
+                }
+            }
+        }
+
+        Ok(positions)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Numbering {
+    pub style: NumberingStyle,
+    pub position: NumberingPosition,
+    /// The tweets that get a counter
+    pub positions: Positions,
+    /// The 1-based section of a longer thread that these tweets are,
+    /// written before each number as in `2.3/2.5`, see `sections::split_thread`
+    pub section: Option<usize>,
+}
+
+/// What a counter counts through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberingScope {
+    /// Every tweet of the thread, `1/5` to `5/5`
+    Thread,
+    /// The tweets of a section started by a heading, `2.1/2.3` to `2.3/2.3`
+    /// in the second, numbered when split with `sections::split_thread`
+    Section,
+}
+
+impl FromStr for NumberingScope {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "thread" => Ok(NumberingScope::Thread),
+            "section" => Ok(NumberingScope::Section),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown numbering scope `{}`, expected thread or section.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl Numbering {
+    pub fn new(style: NumberingStyle, position: NumberingPosition) -> Self {
+        Self {
+            style,
+            position,
+            positions: Positions::all(),
+            section: None,
+        }
+    }
+
+    /// The counter for the tweet at 1-based `index`,
+    /// including the space separating it from the text.
+    pub fn render(&self, index: usize, total: usize) -> String {
+        let mut output = String::new();
+        self.render_into(index, total, &mut output);
+
+        output
+    }
+
+    /// Append the counter, with its separating space, to `output`.
+    pub fn render_into(&self, index: usize, total: usize, output: &mut String) {
+        match self.position {
+            NumberingPosition::Prefix => {
+                self.style
+                    .render_in_section(self.section, index, total, output);
+                output.push(' ');
+            }
+            NumberingPosition::Suffix => {
+                output.push(' ');
+                self.style
+                    .render_in_section(self.section, index, total, output);
+            }
+        }
+    }
+
+    /// The room to reserve in every tweet of a thread of `total` tweets,
+    /// so that every tweet has the same budget regardless of its own counter.
+    pub fn reserved_width(&self, total: usize, count: CountMode) -> usize {
+        let section = self
+            .section
+            .map_or(0, |section| self.style.section_width(section));
+
+        self.style.widest(total, count) + section + 1
+    }
+}
+
+impl NumberingStyle {
+    pub fn render(&self, index: usize, total: usize) -> String {
+        let mut output = String::new();
+        self.render_into(index, total, &mut output);
+
+        output
+    }
+
+    pub fn render_into(&self, index: usize, total: usize, output: &mut String) {
+        self.render_in_section(None, index, total, output);
+    }
+
+    // the counter, with both numbers after `section` and a `.` if there is one
+    fn render_in_section(
+        &self,
+        section: Option<usize>,
+        index: usize,
+        total: usize,
+        output: &mut String,
+    ) {
+        let (open, separator, close) = match self {
+            NumberingStyle::Of => ("", " of ", ""),
+            NumberingStyle::Bracketed => ("[", "/", "]"),
+            NumberingStyle::Thread => ("🧵", "/", ""),
+            NumberingStyle::Slash | NumberingStyle::Roman | NumberingStyle::Letters => {
+                ("", "/", "")
+            }
+        };
+
+        output.push_str(open);
+        self.number_into(section, index, output);
+        output.push_str(separator);
+        self.number_into(section, total, output);
+        output.push_str(close);
+    }
+
+    fn number_into(&self, section: Option<usize>, n: usize, output: &mut String) {
+        if let Some(section) = section {
+            self.numeral_into(section, output);
+            output.push('.');
+        }
+        self.numeral_into(n, output);
+    }
+
+    fn numeral_into(&self, n: usize, output: &mut String) {
+        match self {
+            NumberingStyle::Roman => output.push_str(&roman(n)),
+            NumberingStyle::Letters => output.push_str(&letters(n)),
+            // writing to a String cannot fail
+            _ => {
+                let _ = write!(output, "{}", n);
+            }
+        }
+    }
+
+    // the length of a section's number and its `.`, before both numbers of a counter
+    fn section_width(&self, section: usize) -> usize {
+        let numeral = match self {
+            NumberingStyle::Roman => roman_len(section),
+            NumberingStyle::Letters => letters_len(section),
+            _ => decimal_len(section as i64),
+        };
+
+        2 * (numeral + 1)
+    }
+
+    // the length of the longest counter in a thread of `total` tweets
+    fn widest(&self, total: usize, count: CountMode) -> usize {
+        // the index never has more digits than the total
+        let digits = 2 * decimal_len(total as i64);
+
+        match self {
+            NumberingStyle::Slash => digits + "/".len(),
+            NumberingStyle::Of => digits + " of ".len(),
+            NumberingStyle::Bracketed => digits + "[/]".len(),
+            NumberingStyle::Thread => digits + count.count("🧵/"),
+            // "VIII/X" is wider than "X/X", so the index is the widest numeral up to the total
+            NumberingStyle::Roman => widest_roman(total) + 1 + roman_len(total),
+            NumberingStyle::Letters => 2 * letters_len(total) + 1,
+        }
+    }
+}
+
+impl FromStr for NumberingStyle {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slash" | "1/5" => Ok(NumberingStyle::Slash),
+            "of" | "1 of 5" => Ok(NumberingStyle::Of),
+            "bracketed" | "[1/5]" => Ok(NumberingStyle::Bracketed),
+            "thread" | "🧵1/5" => Ok(NumberingStyle::Thread),
+            "roman" => Ok(NumberingStyle::Roman),
+            "letters" => Ok(NumberingStyle::Letters),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown numbering style `{}`, expected one of slash, of, bracketed, thread, roman, letters.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl FromStr for NumberingPosition {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prefix" => Ok(NumberingPosition::Prefix),
+            "suffix" => Ok(NumberingPosition::Suffix),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!(
+                    "Unknown numbering position `{}`, expected prefix or suffix.",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+const NUMERALS: [(usize, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+fn roman(n: usize) -> String {
+    roman_numerals(n).collect()
+}
+
+fn roman_len(n: usize) -> usize {
+    roman_numerals(n).map(str::len).sum()
+}
+
+fn roman_numerals(mut n: usize) -> impl Iterator<Item = &'static str> {
+    NUMERALS.iter().flat_map(move |(value, numeral)| {
+        let count = n / value;
+        n %= value;
+        std::iter::repeat_n(*numeral, count)
+    })
+}
+
+lazy_static! {
+    // the length of the widest Roman numeral from 1 to each n below 1000
+    static ref WIDEST_ROMAN: Vec<usize> = (0..1000)
+        .scan(0, |widest, n| {
+            *widest = roman_len(n).max(*widest);
+            Some(*widest)
+        })
+        .collect();
+}
+
+// the length of the widest Roman numeral from 1 to `n`, or 0 for none.
+// a numeral is an M for each thousand and the numeral of the rest, so the widest either
+// has all of `n`'s thousands, or one fewer and the widest rest, DCCCLXXXVIII
+fn widest_roman(n: usize) -> usize {
+    let (thousands, rest) = (n / 1000, n % 1000);
+    let widest = thousands + WIDEST_ROMAN[rest];
+
+    if thousands > 0 {
+        widest.max(thousands - 1 + WIDEST_ROMAN[999])
+    } else {
+        widest
+    }
+}
+
+// bijective base 26: A..Z, AA..AZ, BA..
+fn letters(mut n: usize) -> String {
+    let mut output = vec![];
+
+    while n > 0 {
+        n -= 1;
+        output.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+
+    output.reverse();
+    String::from_utf8(output).expect("ASCII letters are valid UTF-8")
+}
+
+fn letters_len(mut n: usize) -> usize {
+    let mut len = 0;
+
+    while n > 0 {
+        n = (n - 1) / 26;
+        len += 1;
+    }
+
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_every_style() {
+        let rendered = [
+            NumberingStyle::Slash,
+            NumberingStyle::Of,
+            NumberingStyle::Bracketed,
+            NumberingStyle::Thread,
+            NumberingStyle::Roman,
+            NumberingStyle::Letters,
+        ]
+        .iter()
+        .map(|style| style.render(4, 12))
+        .collect::<Vec<String>>();
+
+        assert_eq!(
+            rendered,
+            vec!["4/12", "4 of 12", "[4/12]", "🧵4/12", "IV/XII", "D/L"]
+        );
+    }
+
+    #[test]
+    fn it_renders_roman_numerals_and_letters() {
+        assert_eq!(roman(1994), "MCMXCIV");
+        assert_eq!(roman(3888), "MMMDCCCLXXXVIII");
+        assert_eq!(letters(26), "Z");
+        assert_eq!(letters(27), "AA");
+        assert_eq!(letters(702), "ZZ");
+        assert_eq!(letters(703), "AAA");
+    }
+
+    #[test]
+    fn it_reserves_the_widest_counter() {
+        for style in [
+            NumberingStyle::Slash,
+            NumberingStyle::Of,
+            NumberingStyle::Bracketed,
+            NumberingStyle::Thread,
+            NumberingStyle::Roman,
+            NumberingStyle::Letters,
+        ]
+        .iter()
+        {
+            let numbering = Numbering::new(*style, NumberingPosition::Suffix);
+
+            for total in 1..200 {
+                let widest = (1..=total)
+                    .map(|index| numbering.render(index, total).len())
+                    .max()
+                    .unwrap();
+
+                assert!(numbering.reserved_width(total, CountMode::Bytes) >= widest);
+            }
+        }
+
+        let roman = Numbering::new(NumberingStyle::Roman, NumberingPosition::Prefix);
+        assert_eq!(roman.reserved_width(10, CountMode::Bytes), "VIII/X ".len());
+    }
+
+    #[test]
+    fn it_finds_the_widest_roman_numeral_without_measuring_each() {
+        let mut widest = 0;
+
+        for n in 0..6000 {
+            widest = roman_len(n).max(widest);
+            assert_eq!(widest_roman(n), widest, "up to {}", n);
+        }
+    }
+
+    #[test]
+    fn it_numbers_within_a_section() {
+        let mut numbering = Numbering::new(NumberingStyle::Bracketed, NumberingPosition::Prefix);
+        numbering.section = Some(2);
+        assert_eq!(numbering.render(3, 5), "[2.3/2.5] ");
+        assert_eq!(numbering.reserved_width(5, CountMode::Bytes), 10);
+
+        numbering.style = NumberingStyle::Roman;
+        numbering.section = Some(4);
+        assert_eq!(numbering.render(1, 2), "IV.I/IV.II ");
+    }
+
+    #[test]
+    fn it_selects_positions() {
+        let first_and_last = "first,last".parse::<Positions>().unwrap();
+
+        let included = (0..4)
+            .map(|index| first_and_last.includes(index, index == 3))
+            .collect::<Vec<bool>>();
+
+        assert_eq!(included, vec![true, false, false, true]);
+        assert!(!"middle".parse::<Positions>().unwrap().includes(0, true));
+        assert!("last".parse::<Positions>().unwrap().includes(0, true));
+        assert!("all".parse::<Positions>().unwrap().includes(2, false));
+        assert!("first,bogus".parse::<Positions>().is_err());
+    }
+}
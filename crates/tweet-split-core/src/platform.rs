@@ -0,0 +1,138 @@
+// the platforms tweets are written for
+
+use crate::count::CountMode;
+use crate::{SplitOptions, TweetSplitError};
+use std::str::FromStr;
+
+/// A platform, with its own rules for counting length and for what a post may contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    /// twitter-text's weighted length: most scripts count 1 per code point,
+    /// CJK and other wide characters 2, and any emoji sequence 2
+    Twitter,
+    /// Extended grapheme clusters, so anything drawn as one character counts 1
+    Bluesky,
+}
+
+impl FromStr for Platform {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "twitter" => Ok(Platform::Twitter),
+            "bluesky" => Ok(Platform::Bluesky),
+            _ => Err(TweetSplitError::InvalidOption {
+                details: format!("Unknown platform `{}`, expected twitter or bluesky.", s),
+            }),
+        }
+    }
+}
+
+/// A platform's post length limit and the way it counts length,
+/// so that callers don't have to look them up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub max_length: usize,
+    pub count: CountMode,
+    /// The platform to check posts against with `validate::Rules::for_platform`,
+    /// for the platforms there are rules for
+    pub rules: Option<Platform>,
+}
+
+/// Every preset, selectable by name with `Preset::from_str`.
+///
+/// Threads and Discord are counted in UTF-16, as their text fields are.
+/// SMS is a single GSM-7 segment; `sms::segment_options` picks the encoding
+/// and segment size for a given text.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "twitter",
+        max_length: 280,
+        count: CountMode::TwitterWeighted,
+        rules: Some(Platform::Twitter),
+    },
+    Preset {
+        name: "mastodon",
+        max_length: 500,
+        count: CountMode::Mastodon,
+        rules: None,
+    },
+    Preset {
+        name: "bluesky",
+        max_length: 300,
+        count: CountMode::Graphemes,
+        rules: Some(Platform::Bluesky),
+    },
+    Preset {
+        name: "threads",
+        max_length: 500,
+        count: CountMode::Utf16,
+        rules: None,
+    },
+    Preset {
+        name: "discord",
+        max_length: 2000,
+        count: CountMode::Utf16,
+        rules: None,
+    },
+    Preset {
+        name: "sms",
+        max_length: 160,
+        count: CountMode::Gsm7,
+        rules: None,
+    },
+];
+
+impl Preset {
+    /// Options for splitting into posts of this platform.
+    pub fn options(&self) -> SplitOptions {
+        let mut options = SplitOptions::new(self.max_length);
+        options.count = self.count;
+        options
+    }
+}
+
+impl FromStr for Preset {
+    type Err = TweetSplitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PRESETS
+            .iter()
+            .find(|preset| preset.name == s)
+            .copied()
+            .ok_or_else(|| {
+                let names = PRESETS
+                    .iter()
+                    .map(|preset| preset.name)
+                    .collect::<Vec<&str>>();
+                TweetSplitError::InvalidOption {
+                    details: format!(
+                        "Unknown platform `{}`, expected one of {}.",
+                        s,
+                        names.join(", ")
+                    ),
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_with_options;
+
+    #[test]
+    fn it_splits_for_a_platform() {
+        let bluesky = "bluesky".parse::<Preset>().unwrap();
+        let text = "🧵 ".repeat(200);
+
+        // 400 graphemes, 1,400 bytes
+        assert_eq!(
+            split_with_options(&text, &bluesky.options()).unwrap().len(),
+            2
+        );
+        assert_eq!(bluesky.rules, Some(Platform::Bluesky));
+        assert!("myspace".parse::<Preset>().is_err());
+    }
+}
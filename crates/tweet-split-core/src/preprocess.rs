@@ -0,0 +1,973 @@
+// transformations applied to the source text before it is split
+
+use crate::count::CountMode;
+use crate::entities::{self, EntityKind};
+use crate::fences::next_fence;
+use crate::TweetSplitError;
+use lazy_static::*;
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+const COMMENT_START: &str = "<!--";
+const COMMENT_END: &str = "-->";
+const LINE_COMMENT: &str = "//~";
+
+/// A cleanup applied to the source text before it is split.
+pub trait TextFilter: std::fmt::Debug {
+    /// The name the filter is chosen by, like `html`
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError>;
+}
+
+/// Filters run in order, each on the output of the last.
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    filters: Vec<Box<dyn TextFilter>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `filter` to the end of the pipeline.
+    pub fn push(&mut self, filter: Box<dyn TextFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// Whether a filter called `name` is in the pipeline.
+    pub fn contains(&self, name: &str) -> bool {
+        self.filters.iter().any(|filter| filter.name() == name)
+    }
+
+    pub fn apply(&self, input: String) -> Result<String, TweetSplitError> {
+        self.filters
+            .iter()
+            .try_fold(input, |text, filter| filter.apply(&text))
+    }
+}
+
+/// Trims whitespace from the ends of the text and of every line.
+#[derive(Clone, Copy, Debug)]
+pub struct Trim;
+
+impl TextFilter for Trim {
+    fn name(&self) -> &'static str {
+        "trim"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(input
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .trim()
+            .to_string())
+    }
+}
+
+/// Turns Windows (`\r\n`) and old Mac (`\r`) line endings into `\n`.
+#[derive(Clone, Copy, Debug)]
+pub struct NormalizeNewlines;
+
+impl TextFilter for NormalizeNewlines {
+    fn name(&self) -> &'static str {
+        "newlines"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(input.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+}
+
+/// See `strip_html`.
+#[derive(Clone, Copy, Debug)]
+pub struct StripHtml;
+
+impl TextFilter for StripHtml {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(strip_html(input))
+    }
+}
+
+/// See `strip_comments`.
+#[derive(Clone, Copy, Debug)]
+pub struct StripComments;
+
+impl TextFilter for StripComments {
+    fn name(&self) -> &'static str {
+        "comments"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(strip_comments(input))
+    }
+}
+
+/// See `substitute_variables`.
+/// Names are looked up in `definitions`, then, if `environment` is set,
+/// in the environment, except in a build with the `pure` feature, which never reads it.
+#[derive(Clone, Debug, Default)]
+pub struct SubstituteVariables {
+    pub definitions: HashMap<String, String>,
+    pub environment: bool,
+}
+
+impl TextFilter for SubstituteVariables {
+    fn name(&self) -> &'static str {
+        "substitute"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        substitute_variables(input, |name| {
+            self.definitions
+                .get(name)
+                .cloned()
+                .or_else(|| self.environment.then(|| environment_variable(name))?)
+        })
+    }
+}
+
+#[cfg(not(feature = "pure"))]
+fn environment_variable(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+#[cfg(feature = "pure")]
+fn environment_variable(_name: &str) -> Option<String> {
+    None
+}
+
+/// Moves the first link to the end of the text.
+///
+/// The first link in a tweet gets a preview card, which is often better
+/// at the end of a thread than competing with its opening tweet.
+#[derive(Clone, Copy, Debug)]
+pub struct LinkLast;
+
+impl TextFilter for LinkLast {
+    fn name(&self) -> &'static str {
+        "link-last"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(match entities::links(input).next() {
+            Some(link) => {
+                let mut output = without(input, std::slice::from_ref(&link))
+                    .trim_end()
+                    .to_string();
+                if !output.is_empty() {
+                    output.push(' ');
+                }
+                output.push_str(&input[link]);
+                output
+            }
+            None => input.to_string(),
+        })
+    }
+}
+
+/// Drops every repeat of a link after its first appearance,
+/// so that later tweets don't repeat its preview card.
+#[derive(Clone, Copy, Debug)]
+pub struct DedupeLinks;
+
+impl TextFilter for DedupeLinks {
+    fn name(&self) -> &'static str {
+        "dedupe-links"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        let mut seen = HashSet::new();
+        let repeats = entities::links(input)
+            .filter(|link| !seen.insert(&input[link.clone()]))
+            .collect::<Vec<Range<usize>>>();
+
+        Ok(without(input, &repeats))
+    }
+}
+
+/// Replaces the bullets of Markdown lists, `-`, `*`, and `+`, with `bullet`,
+/// and with `keycaps` set, the numbers of ordered lists like `2.` with keycap emoji like `2️⃣`,
+/// which read better than plain text markup on platforms that don't render it.
+///
+/// Lines in fenced code blocks are left alone.
+#[derive(Clone, Debug)]
+pub struct ListBullets {
+    pub bullet: String,
+    pub keycaps: bool,
+}
+
+impl Default for ListBullets {
+    fn default() -> Self {
+        Self {
+            bullet: "•".to_string(),
+            keycaps: false,
+        }
+    }
+}
+
+impl TextFilter for ListBullets {
+    fn name(&self) -> &'static str {
+        "bullets"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        let mut output = String::with_capacity(input.len());
+        let mut fence = None;
+
+        for line in input.split_inclusive('\n') {
+            let in_code = fence.is_some();
+            fence = next_fence(fence, line);
+            if in_code || fence.is_some() {
+                output.push_str(line);
+                continue;
+            }
+
+            let content = line.trim_start();
+            let indent = &line[..line.len() - content.len()];
+            let marker_len = content.find([' ', '\t']).unwrap_or(content.len());
+            let (marker, rest) = content.split_at(marker_len);
+            let rest = rest.trim_start_matches([' ', '\t']);
+            // `* * *` and `- - -` are rules, not lists
+            let is_rule = rest
+                .trim_end()
+                .chars()
+                .all(|c| c == ' ' || marker.starts_with(c));
+
+            let number = marker
+                .strip_suffix(['.', ')'])
+                .filter(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()));
+            let replacement = match number {
+                Some(number) if self.keycaps && !rest.trim().is_empty() => Some(keycaps(number)),
+                _ if ["-", "*", "+"].contains(&marker) && !rest.trim().is_empty() && !is_rule => {
+                    Some(self.bullet.clone())
+                }
+                _ => None,
+            };
+
+            match replacement {
+                Some(replacement) => {
+                    output.push_str(indent);
+                    output.push_str(&replacement);
+                    output.push(' ');
+                    output.push_str(rest);
+                }
+                None => output.push_str(line),
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Rewrites hashtags of several words run together in CamelCase, like `#rustlangcommunity`
+/// as `#RustLangCommunity`, so that screen readers read them word by word.
+///
+/// A hashtag that is one of `hashtags` ignoring case, with or without its `#`,
+/// takes that spelling. Otherwise a hashtag all in lowercase is broken into the
+/// fewest of `words`, in lowercase, that make it up exactly, with runs of digits as words of their own,
+/// and each capitalized. Hashtags that don't break into at least two words,
+/// already have capitals, or are in fenced code are left alone.
+#[derive(Clone, Debug, Default)]
+pub struct CamelHashtags {
+    pub hashtags: Vec<String>,
+    pub words: HashSet<String>,
+}
+
+impl TextFilter for CamelHashtags {
+    fn name(&self) -> &'static str {
+        "camel-hashtags"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        let mut output = String::with_capacity(input.len());
+        let mut fence = None;
+
+        for line in input.split_inclusive('\n') {
+            let in_code = fence.is_some();
+            fence = next_fence(fence, line);
+            if in_code || fence.is_some() {
+                output.push_str(line);
+                continue;
+            }
+
+            let mut copied = 0;
+            for entity in entities::extract(line, CountMode::Bytes) {
+                if entity.kind != EntityKind::Hashtag {
+                    continue;
+                }
+                if let Some(camel) = self.camel_case(&entity.text(line)[1..]) {
+                    output.push_str(&line[copied..entity.bytes.start + 1]);
+                    output.push_str(&camel);
+                    copied = entity.bytes.end;
+                }
+            }
+            output.push_str(&line[copied..]);
+        }
+
+        Ok(output)
+    }
+}
+
+impl CamelHashtags {
+    // `tag`, without its `#`, in CamelCase, if it should change
+    fn camel_case(&self, tag: &str) -> Option<String> {
+        let supplied = self
+            .hashtags
+            .iter()
+            .map(|hashtag| hashtag.trim_start_matches('#'))
+            .find(|hashtag| hashtag.to_lowercase() == tag.to_lowercase());
+        if let Some(supplied) = supplied {
+            return Some(supplied.to_string()).filter(|supplied| supplied != tag);
+        }
+
+        if tag
+            .chars()
+            .any(|c| !(c.is_lowercase() || c.is_ascii_digit()))
+        {
+            return None;
+        }
+
+        let words = self.segment(tag)?;
+        if words.len() < 2 {
+            return None;
+        }
+
+        Some(
+            words
+                .iter()
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    // the fewest words and runs of digits that make up `tag` exactly
+    fn segment<'a>(&self, tag: &'a str) -> Option<Vec<&'a str>> {
+        // the fewest words making up the tag up to each byte offset,
+        // with where the last of them starts
+        let mut best: Vec<Option<(usize, usize)>> = vec![None; tag.len() + 1];
+        best[0] = Some((0, 0));
+
+        for start in 0..tag.len() {
+            let words = match best[start] {
+                Some((words, _)) if tag.is_char_boundary(start) => words,
+                _ => continue,
+            };
+            let rest = &tag[start..];
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let ends = rest
+                .char_indices()
+                .map(|(offset, c)| offset + c.len_utf8())
+                .filter(|end| {
+                    if digits > 0 {
+                        *end == digits
+                    } else {
+                        self.words.contains(&rest[..*end])
+                    }
+                });
+
+            for end in ends {
+                let end = start + end;
+                if best[end].is_none_or(|(most, _)| words + 1 < most) {
+                    best[end] = Some((words + 1, start));
+                }
+            }
+        }
+
+        let mut words = vec![];
+        let mut end = tag.len();
+        while end > 0 {
+            let (_, start) = best[end]?;
+            words.push(&tag[start..end]);
+            end = start;
+        }
+        words.reverse();
+
+        Some(words)
+    }
+}
+
+/// See `style_emphasis`.
+#[derive(Clone, Copy, Debug)]
+pub struct StyleEmphasis;
+
+impl TextFilter for StyleEmphasis {
+    fn name(&self) -> &'static str {
+        "styles"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        Ok(style_emphasis(input))
+    }
+}
+
+/// Write Markdown `**bold**`, `*italic*`, and `***both***` (or with `_`) in the
+/// sans-serif letters and digits of Unicode's mathematical alphanumeric symbols,
+/// like 𝗯𝗼𝗹𝗱 and 𝘪𝘵𝘢𝘭𝘪𝘤, since platforms without rich text show them as styled.
+///
+/// Each styled character is outside the Basic Multilingual Plane, so it counts 2
+/// in UTF-16 and Twitter's weighted count. Screen readers read them as
+/// mathematical symbols, one at a time, or skip them, so they are best kept short.
+/// Code spans, fenced code, and words in emphasis that are links, hashtags,
+/// mentions, or cashtags are left unstyled, since styling would break them.
+pub fn style_emphasis(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut fence = None;
+
+    for line in input.split_inclusive('\n') {
+        let in_code = fence.is_some();
+        fence = next_fence(fence, line);
+        if in_code || fence.is_some() {
+            output.push_str(line);
+            continue;
+        }
+
+        let styled = EMPHASIS.replace_all(line, |captures: &Captures| {
+            let (text, style) = match (1..=6).find_map(|group| Some((captures.get(group)?, group)))
+            {
+                Some((text, 1)) => (text.as_str(), Style::BoldItalic),
+                Some((text, 2 | 3)) => (text.as_str(), Style::Bold),
+                Some((text, _)) => (text.as_str(), Style::Italic),
+                // a code span
+                None => return captures[0].to_string(),
+            };
+
+            text.split_inclusive(char::is_whitespace)
+                .map(|word| {
+                    let is_entity = word.starts_with(['#', '@', '$']) || word.contains("://");
+                    if is_entity {
+                        word.to_string()
+                    } else {
+                        word.chars().map(|c| style.apply(c)).collect()
+                    }
+                })
+                .collect::<String>()
+        });
+        output.push_str(&styled);
+    }
+
+    output
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Style {
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+impl Style {
+    // `c` in the sans-serif alphabet of this style, if it has one
+    fn apply(&self, c: char) -> char {
+        let (capital, small, digit) = match self {
+            Style::Bold => (0x1D5D4, 0x1D5EE, Some(0x1D7EC)),
+            Style::Italic => (0x1D608, 0x1D622, None),
+            Style::BoldItalic => (0x1D63C, 0x1D656, Some(0x1D7EC)),
+        };
+        let styled = match c {
+            'A'..='Z' => Some(capital + (c as u32 - 'A' as u32)),
+            'a'..='z' => Some(small + (c as u32 - 'a' as u32)),
+            '0'..='9' => digit.map(|zero| zero + (c as u32 - '0' as u32)),
+            _ => None,
+        };
+
+        styled.and_then(char::from_u32).unwrap_or(c)
+    }
+}
+
+lazy_static! {
+    // a code span, kept as it is, or emphasis, whose text starts and ends with non-whitespace
+    static ref EMPHASIS: Regex = Regex::new(concat!(
+        r"`[^`]*`",
+        r"|\*\*\*(\S(?:.*?\S)?)\*\*\*",
+        r"|\*\*(\S(?:.*?\S)?)\*\*",
+        r"|\b__(\S(?:.*?\S)?)__\b",
+        r"|\*(\S(?:.*?\S)?)\*",
+        r"|\b_(\S(?:.*?\S)?)_\b",
+    ))
+    .unwrap();
+}
+
+// `number` in keycap emoji, like 1️⃣2️⃣, or 🔟 for 10
+fn keycaps(number: &str) -> String {
+    if number == "10" {
+        return "🔟".to_string();
+    }
+
+    number
+        .chars()
+        .flat_map(|digit| [digit, '\u{FE0F}', '\u{20E3}'])
+        .collect()
+}
+
+// `input` without the ordered `ranges` and the spaces before each of them,
+// or after it if it starts a line
+fn without(input: &str, ranges: &[Range<usize>]) -> String {
+    let is_space = |c: char| c == ' ' || c == '\t';
+    let mut output = String::with_capacity(input.len());
+    let mut copied_to = 0;
+
+    for range in ranges {
+        let before = input[copied_to..range.start].trim_end_matches(is_space);
+        output.push_str(before);
+
+        copied_to = range.end;
+        if before.is_empty() || before.ends_with('\n') {
+            // a link on a line of its own goes with its line
+            let after = input[range.end..].trim_start_matches(is_space);
+            let after = after.strip_prefix('\n').unwrap_or(after);
+            copied_to = input.len() - after.len();
+        }
+    }
+    output.push_str(&input[copied_to..]);
+
+    output
+}
+
+/// Remove notes-to-self from the source text.
+///
+/// Two comment syntaxes are recognized:
+/// - `<!-- ... -->`, which may span multiple lines.
+///   An unterminated `<!--` comments out the rest of the input.
+/// - lines whose first non-whitespace characters are `//~`,
+///   which are removed entirely, including their line ending.
+pub fn strip_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut in_block_comment = false;
+
+    for line in input.split_inclusive('\n') {
+        if !in_block_comment && line.trim_start().starts_with(LINE_COMMENT) {
+            continue;
+        }
+
+        let mut rest = line;
+
+        loop {
+            if in_block_comment {
+                match rest.find(COMMENT_END) {
+                    Some(end) => {
+                        rest = &rest[end + COMMENT_END.len()..];
+                        in_block_comment = false;
+                    }
+                    None => break,
+                }
+            } else {
+                match rest.find(COMMENT_START) {
+                    Some(start) => {
+                        output.push_str(&rest[..start]);
+                        rest = &rest[start + COMMENT_START.len()..];
+                        in_block_comment = true;
+                    }
+                    None => {
+                        output.push_str(rest);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Remove HTML markup, for text pasted from a web page.
+///
+/// Tags and `<!-- -->` comments are dropped, except that `<br>` becomes a line break
+/// and `</p>` a blank line, and entities like `&amp;` and `&#8217;` are decoded.
+/// A `<` that does not start a tag, like in `1 < 2`, is kept.
+pub fn strip_html(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(open) = rest.find('<') {
+        push_decoded(&mut output, &rest[..open]);
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix(COMMENT_START) {
+            rest = match comment.find(COMMENT_END) {
+                Some(end) => &comment[end + COMMENT_END.len()..],
+                None => "",
+            };
+            continue;
+        }
+
+        let starts_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+
+        match rest.find('>').filter(|_| starts_tag) {
+            Some(close) => {
+                let name = rest[1..close]
+                    .split(|c: char| c.is_whitespace() || c == '>')
+                    .next()
+                    .unwrap_or("")
+                    .trim_end_matches('/')
+                    .to_ascii_lowercase();
+
+                match name.as_str() {
+                    "br" => output.push('\n'),
+                    "/p" => output.push_str("\n\n"),
+                    _ => (),
+                }
+                rest = &rest[close + 1..];
+            }
+            None => {
+                output.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    push_decoded(&mut output, rest);
+
+    output
+}
+
+// push `text` onto `output`, decoding HTML entities;
+// unknown or malformed entities are kept as they are
+fn push_decoded(output: &mut String, text: &str) {
+    let mut rest = text;
+
+    while let Some(ampersand) = rest.find('&') {
+        output.push_str(&rest[..ampersand]);
+        rest = &rest[ampersand..];
+
+        let decoded = rest
+            .find(';')
+            .filter(|semicolon| *semicolon <= 10)
+            .and_then(|semicolon| Some((entity(&rest[1..semicolon])?, semicolon)));
+
+        match decoded {
+            Some((c, semicolon)) => {
+                output.push(c);
+                rest = &rest[semicolon + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+}
+
+fn entity(name: &str) -> Option<char> {
+    let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        u32::from_str_radix(hex, 16).ok()?
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        decimal.parse().ok()?
+    } else {
+        return match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => None,
+        };
+    };
+
+    char::from_u32(code)
+}
+
+/// Replace `${NAME}` references with the value returned by `lookup`.
+///
+/// Names must start with an ASCII letter or underscore,
+/// followed by ASCII letters, digits, or underscores.
+/// `$${NAME}` produces a literal `${NAME}`,
+/// and anything else that is not a well-formed reference is left untouched.
+/// A reference for which `lookup` returns `None` is an error.
+pub fn substitute_variables<F>(input: &str, lookup: F) -> Result<String, TweetSplitError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(dollar) = rest.find('$') {
+        output.push_str(&rest[..dollar]);
+        rest = &rest[dollar..];
+
+        if rest.starts_with("$${") {
+            output.push_str("${");
+            rest = &rest[3..];
+            continue;
+        }
+
+        match variable_reference(rest) {
+            Some((name, reference_length)) => {
+                let value = lookup(name).ok_or_else(|| TweetSplitError::UndefinedVariable {
+                    name: name.to_string(),
+                })?;
+                output.push_str(&value);
+                rest = &rest[reference_length..];
+            }
+            None => {
+                output.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+// given text starting with `$`, return the variable name
+// and the byte length of the whole `${NAME}` reference
+fn variable_reference(text: &str) -> Option<(&str, usize)> {
+    let body = text.strip_prefix("${")?;
+    let end = body.find('}')?;
+    let name = &body[..end];
+
+    let mut chars = name.chars();
+    let valid_start = chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or(false);
+
+    if valid_start && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Some((name, end + 3))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count::Counter;
+    use std::collections::HashMap;
+
+    #[test]
+    fn it_strips_block_comments() {
+        let input = "hello <!-- not posted --> world";
+
+        assert_eq!(strip_comments(input), "hello  world");
+    }
+
+    #[test]
+    fn it_strips_multiline_block_comments() {
+        let input = "first\n<!-- a note\nthat spans\nlines -->second";
+
+        assert_eq!(strip_comments(input), "first\nsecond");
+    }
+
+    #[test]
+    fn it_strips_line_comments() {
+        let input = "first\n//~ remember to add a link\n  //~ indented note\nsecond\n";
+
+        assert_eq!(strip_comments(input), "first\nsecond\n");
+    }
+
+    #[test]
+    fn it_strips_unterminated_block_comments_to_the_end() {
+        let input = "kept <!-- dropped\nalso dropped";
+
+        assert_eq!(strip_comments(input), "kept ");
+    }
+
+    #[test]
+    fn it_leaves_line_comment_markers_inside_lines() {
+        let input = "a url like http://~example is fine //~ here";
+
+        assert_eq!(strip_comments(input), input);
+    }
+
+    #[test]
+    fn it_strips_html() {
+        let input =
+            "<p>Fish &amp; chips</p><p>are <b>great</b>,<br/>it&#8217;s true <!-- <p> --></p>";
+
+        assert_eq!(
+            strip_html(input),
+            "Fish & chips\n\nare great,\nit\u{2019}s true \n\n"
+        );
+    }
+
+    #[test]
+    fn it_keeps_text_that_only_looks_like_html() {
+        let input = "if 1 < 2 && x > 3 then &unknown; or & so on";
+
+        assert_eq!(strip_html(input), input);
+    }
+
+    #[test]
+    fn it_moves_the_first_link_to_the_end() {
+        assert_eq!(
+            LinkLast
+                .apply("Read https://example.com/post first, then https://example.com/more.")
+                .unwrap(),
+            "Read first, then https://example.com/more. https://example.com/post"
+        );
+        assert_eq!(
+            LinkLast
+                .apply("https://example.com/post\nnew post")
+                .unwrap(),
+            "new post https://example.com/post"
+        );
+        assert_eq!(LinkLast.apply("no links").unwrap(), "no links");
+    }
+
+    #[test]
+    fn it_drops_repeated_links() {
+        assert_eq!(
+            DedupeLinks
+                .apply("See https://example.com/a. Again, https://example.com/a and https://example.com/b")
+                .unwrap(),
+            "See https://example.com/a. Again, and https://example.com/b"
+        );
+    }
+
+    #[test]
+    fn it_runs_filters_in_order() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Box::new(NormalizeNewlines));
+        pipeline.push(Box::new(StripHtml));
+        pipeline.push(Box::new(Trim));
+
+        let output = pipeline
+            .apply("  <i>one</i>  \r\ntwo &lt;3\r\n\r\n".to_string())
+            .unwrap();
+
+        assert_eq!(output, "one\ntwo <3");
+        assert!(pipeline.contains("html"));
+        assert!(!pipeline.contains("comments"));
+    }
+
+    #[test]
+    fn it_substitutes_definitions_before_the_environment() {
+        let filter = SubstituteVariables {
+            definitions: vec![("PATH".to_string(), "defined".to_string())]
+                .into_iter()
+                .collect(),
+            environment: true,
+        };
+
+        assert_eq!(filter.apply("${PATH}").unwrap(), "defined");
+        assert!(SubstituteVariables::default().apply("${PATH}").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "pure")]
+    fn it_never_reads_the_environment_when_pure() {
+        let filter = SubstituteVariables {
+            definitions: HashMap::new(),
+            environment: true,
+        };
+
+        assert!(filter.apply("${PATH}").is_err());
+    }
+
+    #[test]
+    fn it_substitutes_variables() {
+        let mut vars = HashMap::new();
+        vars.insert("VERSION", "1.4.0");
+        vars.insert("DATE_2", "today");
+
+        let output = substitute_variables("v${VERSION} ships ${DATE_2}!", |name| {
+            vars.get(name).map(|value| value.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(output, "v1.4.0 ships today!");
+    }
+
+    #[test]
+    fn it_errors_on_undefined_variables() {
+        let result = substitute_variables("hello ${MISSING}", |_| None);
+
+        match result {
+            Err(TweetSplitError::UndefinedVariable { name }) => assert_eq!(name, "MISSING"),
+            other => panic!("expected an undefined variable error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_leaves_malformed_references_and_escapes_alone() {
+        let input = "costs $5, ${not valid}, ${1ABC}, $${ESCAPED}, ${unterminated";
+
+        let output = substitute_variables(input, |_| None).unwrap();
+
+        assert_eq!(
+            output,
+            "costs $5, ${not valid}, ${1ABC}, ${ESCAPED}, ${unterminated"
+        );
+    }
+
+    #[test]
+    fn it_replaces_list_bullets() {
+        let input = "todo:\n- eggs\n  * milk\n---\n* * *\n1. first\n10) tenth\n```\n- code\n```\n";
+        let bullets = ListBullets::default();
+        let keycaps = ListBullets {
+            bullet: "👉".to_string(),
+            keycaps: true,
+        };
+
+        assert_eq!(
+            bullets.apply(input).unwrap(),
+            "todo:\n• eggs\n  • milk\n---\n* * *\n1. first\n10) tenth\n```\n- code\n```\n"
+        );
+        assert_eq!(
+            keycaps.apply(input).unwrap(),
+            "todo:\n👉 eggs\n  👉 milk\n---\n* * *\n1\u{FE0F}\u{20E3} first\n🔟 tenth\n```\n- code\n```\n"
+        );
+    }
+
+    #[test]
+    fn it_styles_emphasis() {
+        assert_eq!(
+            style_emphasis("**Bold 42** and *it* or ***both***"),
+            "𝗕𝗼𝗹𝗱 𝟰𝟮 and 𝘪𝘵 or 𝙗𝙤𝙩𝙝"
+        );
+        assert_eq!(
+            style_emphasis("keep `*code*`, snake_case_names, and **#rust @ckampfe**"),
+            "keep `*code*`, snake_case_names, and #rust @ckampfe"
+        );
+        assert_eq!(
+            style_emphasis("```\n**no**\n```\n* list"),
+            "```\n**no**\n```\n* list"
+        );
+        assert_eq!(CountMode::Utf16.count(&style_emphasis("*a*")), 2);
+    }
+
+    #[test]
+    fn it_camel_cases_hashtags() {
+        let filter = CamelHashtags {
+            hashtags: vec!["#TIL".to_string()],
+            words: ["rust", "rus", "trust", "lang", "community", "conf"]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+        };
+
+        assert_eq!(
+            filter
+                .apply("#rustlangcommunity #rustconf2024 #til #rust #unknownwords #RustLang")
+                .unwrap(),
+            "#RustLangCommunity #RustConf2024 #TIL #rust #unknownwords #RustLang"
+        );
+        assert_eq!(
+            filter.apply("```\n#rustlang\n```\n").unwrap(),
+            "```\n#rustlang\n```\n"
+        );
+    }
+}
@@ -0,0 +1,357 @@
+// splitting text with headings a section at a time, so that each section's tweets
+// are numbered on their own, like 2.3/2.5 for the third of five in the second section,
+// or with each heading promoted to a tweet of its own that introduces its section
+
+use crate::count::Counter;
+use crate::fences::next_fence;
+use crate::thread::Thread;
+use crate::{PreparedText, SplitOptions, TweetSplitError};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// How a heading promoted to a tweet of its own stands out from the tweets after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeadingStyle {
+    /// After a marker and a space, like `▶ Keynote`
+    Marker(String),
+    /// In capitals, like `KEYNOTE`
+    Uppercase,
+}
+
+impl HeadingStyle {
+    /// `heading`, a heading's line without its `#`s, as its promoted tweet.
+    pub fn render(&self, heading: &str) -> String {
+        match self {
+            HeadingStyle::Marker(marker) => format!("{} {}", marker, heading),
+            HeadingStyle::Uppercase => heading.to_uppercase(),
+        }
+    }
+}
+
+impl FromStr for HeadingStyle {
+    type Err = TweetSplitError;
+
+    /// `uppercase`, or any other text as the marker.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Err(TweetSplitError::InvalidOption {
+                details: "A heading style needs a marker like `▶`, or `uppercase`.".to_string(),
+            }),
+            "uppercase" => Ok(HeadingStyle::Uppercase),
+            marker => Ok(HeadingStyle::Marker(marker.to_string())),
+        }
+    }
+}
+
+/// The byte range of each section of `input`: one from each Markdown heading,
+/// like `## Keynote`, to the next, and one for any text before the first.
+///
+/// Lines in fenced code blocks are never headings.
+pub fn sections(input: &str) -> Vec<Range<usize>> {
+    let mut starts = vec![0];
+    let mut fence = None;
+    let mut line_start = 0;
+
+    for line in input.split_inclusive('\n') {
+        let in_code = fence.is_some();
+        fence = next_fence(fence, line);
+
+        if !in_code && fence.is_none() && is_heading(line) && line_start > 0 {
+            starts.push(line_start);
+        }
+        line_start += line.len();
+    }
+
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(std::iter::once(&input.len())))
+        .map(|(start, end)| *start..*end)
+        .filter(|section| !input[section.clone()].trim().is_empty())
+        .collect()
+}
+
+/// Like `split_thread`, but splitting each section of `input` in its own tweets,
+/// with any numbering restarting in each, as `2.1/2.3` in the second.
+///
+/// Only the counter restarts. The end marker, the sign-off, and whatever else is for
+/// the thread's last tweet, like hashtags on `last`, come once, in the last section.
+pub fn split_thread(input: &str, options: &SplitOptions) -> Result<Thread, TweetSplitError> {
+    split_thread_with(input, options, crate::split_thread)
+}
+
+/// Like `split_thread`, splitting each section with `split`, like `fences::split_thread`.
+pub fn split_thread_with<F>(
+    input: &str,
+    options: &SplitOptions,
+    split: F,
+) -> Result<Thread, TweetSplitError>
+where
+    F: Fn(&str, &SplitOptions) -> Result<Thread, TweetSplitError>,
+{
+    let mut thread = Thread {
+        texts: vec![],
+        tweets: vec![],
+        ranges: vec![],
+    };
+    let sections = sections(input);
+    let mut section_options = options.clone();
+
+    for (number, range) in sections.iter().cloned().enumerate() {
+        if let Some(numbering) = &mut section_options.numbering {
+            numbering.section = Some(number + 1);
+        }
+        let last = number + 1 == sections.len();
+        section_options.continues = options.continues || !last;
+        section_options.sign_off = options.sign_off.clone().filter(|_| last);
+
+        let section =
+            split(&input[range.clone()], &section_options).map_err(|error| match error {
+                TweetSplitError::WordTooLong {
+                    word,
+                    offset,
+                    length,
+                    budget,
+                } => TweetSplitError::WordTooLong {
+                    word,
+                    offset: range.start + offset,
+                    length,
+                    budget,
+                },
+                error => error,
+            })?;
+
+        thread.texts.extend(section.texts);
+        thread.tweets.extend(section.tweets);
+        thread.ranges.extend(
+            section
+                .ranges
+                .into_iter()
+                .map(|tweet| range.start + tweet.start..range.start + tweet.end),
+        );
+    }
+
+    Ok(thread)
+}
+
+// a heading's tweet, or the text after it up to the next heading
+enum Piece {
+    Heading { text: String, range: Range<usize> },
+    Body(Range<usize>),
+}
+
+/// Like `split_thread`, but with each Markdown heading in a tweet of its own,
+/// emphasized with `style`, which the text up to the next heading follows.
+///
+/// The thread is still numbered as one. Paragraphs are reflowed,
+/// and there's no sign-off, since each section is packed on its own.
+pub fn split_promoting_headings(
+    input: &str,
+    options: &SplitOptions,
+    style: &HeadingStyle,
+) -> Result<Thread, TweetSplitError> {
+    let options = SplitOptions {
+        sign_off: None,
+        ..options.clone()
+    };
+
+    let mut pieces = vec![];
+    for section in sections(input) {
+        let text = &input[section.clone()];
+        let first_line = text.split_inclusive('\n').next().unwrap_or("");
+        let body_start = if is_heading(first_line) {
+            let heading = heading_text(first_line);
+            pieces.push(Piece::Heading {
+                text: style.render(heading),
+                range: section.start..section.start + first_line.trim_end().len(),
+            });
+            section.start + first_line.len()
+        } else {
+            section.start
+        };
+
+        if !input[body_start..section.end].trim().is_empty() {
+            pieces.push(Piece::Body(body_start..section.end));
+        }
+    }
+
+    // decorations can widen as the thread grows, so pack for a range of totals
+    // until the count falls inside it, as `PreparedText::reflow` does
+    let mut totals = 1..=1;
+    let mut first_attempt = true;
+
+    loop {
+        let mut tweets: Vec<(String, Range<usize>)> = vec![];
+
+        for (number, piece) in pieces.iter().enumerate() {
+            let last_piece = number + 1 == pieces.len();
+
+            match piece {
+                Piece::Heading { text, range } => {
+                    let index = tweets.len();
+                    let budget = options.budget_within(index, totals.clone(), last_piece)?;
+                    let length = options.count.count(text);
+
+                    if length > budget {
+                        return Err(TweetSplitError::MaxTweetLengthTooShort {
+                            details: format!(
+                                "The heading `{}` is {} long, more than the {} available in its tweet.",
+                                text, length, budget
+                            ),
+                        });
+                    }
+                    tweets.push((text.clone(), range.clone()));
+                }
+                Piece::Body(range) => {
+                    let base = tweets.len();
+                    let prepared = PreparedText::new(&input[range.clone()], &options);
+                    let packed = prepared.pack_by_strategy(&options, |index, is_last| {
+                        options.budget_within(base + index, totals.clone(), is_last && last_piece)
+                    })?;
+
+                    for (start, end) in packed {
+                        let start = range.start + prepared.leading + start;
+                        let end = range.start + prepared.leading + end;
+                        tweets.push((input[start..end].to_string(), start..end));
+                    }
+                }
+            }
+        }
+
+        let count = tweets.len();
+        if totals.contains(&count) || tweets.is_empty() || !options.depends_on_total() {
+            let tweets_rendered = tweets
+                .iter()
+                .enumerate()
+                .map(|(index, (text, _))| options.render(index, count, text))
+                .collect::<Result<Vec<String>, TweetSplitError>>()?;
+            let (texts, ranges) = tweets.into_iter().unzip();
+
+            return Ok(Thread {
+                texts,
+                tweets: tweets_rendered,
+                ranges,
+            });
+        }
+
+        totals = if first_attempt {
+            count..=count
+        } else {
+            *totals.start().min(&count)..=*totals.end().max(&count)
+        };
+        first_attempt = false;
+    }
+}
+
+// a heading's line without the `#`s before it or any closing ones after
+fn heading_text(line: &str) -> &str {
+    let text = line.trim().trim_start_matches('#').trim();
+    let closed = text.trim_end_matches('#');
+
+    if closed.is_empty() || closed.ends_with([' ', '\t']) {
+        closed.trim_end()
+    } else {
+        text
+    }
+}
+
+// whether `line` is an ATX heading, one to six `#` and a space
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start_matches(' ');
+    let level = line.len() - line.trim_start_matches('#').len();
+
+    (1..=6).contains(&level) && line[level..].starts_with([' ', '\t', '\n'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numbering::{Numbering, NumberingPosition, NumberingStyle};
+
+    const NOTES: &str = "notes from the conference\n\n\
+        ## Keynote\nthe future is plural and so are the futures\n\n\
+        ```sh\n# not a heading\n```\n\
+        ## Lunch\nsoup";
+
+    #[test]
+    fn it_finds_a_section_at_each_heading() {
+        let sections = sections(NOTES)
+            .into_iter()
+            .map(|section| NOTES[section].trim())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0], "notes from the conference");
+        assert!(sections[1].starts_with("## Keynote") && sections[1].ends_with("```"));
+        assert_eq!(sections[2], "## Lunch\nsoup");
+        assert!(!is_heading("#hashtag"));
+    }
+
+    #[test]
+    fn it_numbers_each_section_on_its_own() {
+        let mut options = SplitOptions::new(40);
+        options.numbering = Some(Numbering::new(
+            NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        let thread = split_thread(NOTES, &options).unwrap();
+
+        let counters = thread
+            .tweets
+            .iter()
+            .map(|tweet| tweet.rsplit(' ').next().unwrap())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            counters,
+            vec!["1.1/1.1", "2.1/2.3", "2.2/2.3", "2.3/2.3", "3.1/3.1"]
+        );
+        assert_eq!(&NOTES[thread.ranges[4].clone()], "## Lunch\nsoup");
+    }
+
+    #[test]
+    fn it_ends_only_the_last_section() {
+        let mut options = SplitOptions::new(40);
+        options.numbering = Some(Numbering::new(
+            NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        options.end_marker = Some("/end".to_string());
+        options.sign_off = Some(crate::SignOff::Lines(1));
+        let thread = split_thread(&format!("{}\n— me", NOTES), &options).unwrap();
+
+        let ends = thread
+            .tweets
+            .iter()
+            .filter(|tweet| tweet.ends_with("/end"))
+            .collect::<Vec<&String>>();
+
+        assert_eq!(ends, vec!["— me 3.2/3.2 /end"]);
+        assert_eq!(thread.tweets.len(), 6);
+        assert_eq!(thread.tweets[4], "## Lunch\nsoup 3.1/3.2");
+    }
+
+    #[test]
+    fn it_promotes_headings_to_their_own_tweets() {
+        let mut options = SplitOptions::new(40);
+        options.numbering = Some(Numbering::new(
+            NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        let style = "▶".parse::<HeadingStyle>().unwrap();
+        let thread = split_promoting_headings(NOTES, &options, &style).unwrap();
+
+        assert_eq!(thread.tweets.len(), 6);
+        assert_eq!(thread.tweets[0], "notes from the conference 1/6");
+        assert_eq!(thread.tweets[1], "▶ Keynote 2/6");
+        assert_eq!(thread.tweets[4], "▶ Lunch 5/6");
+        assert_eq!(thread.tweets[5], "soup 6/6");
+        assert_eq!(&NOTES[thread.ranges[4].clone()], "## Lunch");
+
+        let uppercase =
+            split_promoting_headings("# Soup ##\nis good", &options, &HeadingStyle::Uppercase);
+        assert_eq!(uppercase.unwrap().tweets, vec!["SOUP 1/2", "is good 2/2"]);
+
+        options.max_tweet_length = 10;
+        assert!(split_promoting_headings("# A long heading\ntext", &options, &style).is_err());
+    }
+}
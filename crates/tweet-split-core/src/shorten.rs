@@ -0,0 +1,117 @@
+// rewriting long links to short ones before splitting,
+// for platforms that count every character of a link
+
+use crate::entities;
+use crate::preprocess::TextFilter;
+use crate::TweetSplitError;
+use std::collections::HashMap;
+
+/// Replaces links with short ones from a fixed mapping,
+/// like the export of a link shortener, so splitting is the same on every run.
+///
+/// The mapping has a long link and its short link on each line,
+/// separated by whitespace. Blank lines and lines starting with `#` are skipped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Shortener {
+    short: HashMap<String, String>,
+    long: HashMap<String, String>,
+}
+
+impl Shortener {
+    pub fn parse(mapping: &str) -> Result<Self, TweetSplitError> {
+        let mut shortener = Shortener::default();
+
+        for (line_number, line) in mapping.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [long, short] => shortener.insert(long, short),
+                _ => {
+                    return Err(TweetSplitError::InvalidInput {
+                        details: format!(
+                            "Line {}: expected a long link and its short link, got `{}`.",
+                            line_number + 1,
+                            line
+                        ),
+                    })
+                }
+            }
+        }
+
+        Ok(shortener)
+    }
+
+    pub fn insert(&mut self, long: &str, short: &str) {
+        self.short.insert(long.to_string(), short.to_string());
+        self.long.insert(short.to_string(), long.to_string());
+    }
+
+    /// The short link for `long`, if the mapping has one.
+    pub fn shorten(&self, long: &str) -> Option<&str> {
+        self.short.get(long).map(String::as_str)
+    }
+
+    /// The original link that was shortened to `short`.
+    pub fn expand(&self, short: &str) -> Option<&str> {
+        self.long.get(short).map(String::as_str)
+    }
+}
+
+impl TextFilter for Shortener {
+    fn name(&self) -> &'static str {
+        "shorten"
+    }
+
+    fn apply(&self, input: &str) -> Result<String, TweetSplitError> {
+        let mut output = String::with_capacity(input.len());
+        let mut copied_to = 0;
+
+        for link in entities::links(input) {
+            if let Some(short) = self.shorten(&input[link.clone()]) {
+                output.push_str(&input[copied_to..link.start]);
+                output.push_str(short);
+                copied_to = link.end;
+            }
+        }
+        output.push_str(&input[copied_to..]);
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPING: &str = "
+        # exported from the shortener
+        https://example.com/2026/10/a-long-article-title  https://ex.co/a1
+        https://example.com/about https://ex.co/b2
+    ";
+
+    #[test]
+    fn it_replaces_mapped_links() {
+        let shortener = Shortener::parse(MAPPING).unwrap();
+
+        assert_eq!(
+            shortener
+                .apply("Read https://example.com/2026/10/a-long-article-title. Or https://example.com/other")
+                .unwrap(),
+            "Read https://ex.co/a1. Or https://example.com/other"
+        );
+        assert_eq!(
+            shortener.expand("https://ex.co/b2"),
+            Some("https://example.com/about")
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_mappings() {
+        let error = Shortener::parse("https://example.com/about\n").unwrap_err();
+
+        assert!(error.to_string().contains("Line 1"));
+    }
+}
@@ -0,0 +1,122 @@
+// splitting text into the segments of one SMS message, as a gateway sends it:
+// in GSM-7 septets if every character has one, otherwise in UCS-2
+
+use crate::count::{CountMode, Counter};
+use crate::SplitOptions;
+
+/// How an SMS message is encoded, which decides how much fits in each segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// The GSM 03.38 alphabet, 7 bits a character,
+    /// with characters from its extension table like `€` taking two
+    Gsm7,
+    /// UTF-16 code units, for a message with any character GSM-7 doesn't have
+    Ucs2,
+}
+
+impl Encoding {
+    /// The encoding a gateway sends `text` in.
+    pub fn of(text: &str) -> Self {
+        if text.chars().all(|c| gsm7_septets(c).is_some()) {
+            Encoding::Gsm7
+        } else {
+            Encoding::Ucs2
+        }
+    }
+
+    /// How segments in this encoding are counted.
+    pub fn count(&self) -> CountMode {
+        match self {
+            Encoding::Gsm7 => CountMode::Gsm7,
+            Encoding::Ucs2 => CountMode::Utf16,
+        }
+    }
+
+    /// The most that fits in a message of one segment.
+    pub fn single_segment(&self) -> usize {
+        match self {
+            Encoding::Gsm7 => 160,
+            Encoding::Ucs2 => 70,
+        }
+    }
+
+    /// The most that fits in each segment of a longer message,
+    /// which gives up room to the header that joins them back together.
+    pub fn concatenated_segment(&self) -> usize {
+        match self {
+            Encoding::Gsm7 => 153,
+            Encoding::Ucs2 => 67,
+        }
+    }
+}
+
+/// Set `options` to split `text` into the segments of one message:
+/// its encoding's count, and room for one segment if all of it fits in one,
+/// otherwise for a segment of a concatenated message.
+///
+/// The encoding is that of `text` alone, so decorations should keep to GSM-7,
+/// or be added to `text` before choosing, to avoid making a GSM-7 message UCS-2.
+pub fn segment_options(text: &str, options: &mut SplitOptions) {
+    let encoding = Encoding::of(text);
+    let count = encoding.count();
+
+    options.count = count;
+    options.max_tweet_length = if count.count(text.trim()) <= encoding.single_segment() {
+        encoding.single_segment()
+    } else {
+        encoding.concatenated_segment()
+    };
+}
+
+/// How many septets `c` takes in GSM-7, if it has a place in it.
+pub fn gsm7_septets(c: char) -> Option<usize> {
+    if GSM7_BASIC.contains(c) {
+        Some(1)
+    } else if GSM7_EXTENSION.contains(c) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// the basic character set of GSM 03.38, without the escape to the extension table
+const GSM7_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+    ¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+// the characters of the extension table, each sent as an escape and itself
+const GSM7_EXTENSION: &str = "\u{000C}^{}\\[~]|€";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_with_options;
+
+    #[test]
+    fn it_counts_extension_characters_twice() {
+        assert_eq!(CountMode::Gsm7.count("price: 5€ [sic]"), 18);
+        assert_eq!(Encoding::of("price: 5€ [sic]"), Encoding::Gsm7);
+        assert_eq!(Encoding::of("smart “quotes”"), Encoding::Ucs2);
+    }
+
+    #[test]
+    fn it_fits_one_segment_or_splits_into_concatenated_ones() {
+        let mut options = SplitOptions::new(0);
+
+        segment_options(&"a".repeat(160), &mut options);
+        assert_eq!(
+            (options.count, options.max_tweet_length),
+            (CountMode::Gsm7, 160)
+        );
+
+        let text = "ab ".repeat(100);
+        segment_options(&text, &mut options);
+        assert_eq!(options.max_tweet_length, 153);
+        assert_eq!(split_with_options(&text, &options).unwrap().len(), 2);
+
+        segment_options(&"日本 ".repeat(30), &mut options);
+        assert_eq!(
+            (options.count, options.max_tweet_length),
+            (CountMode::Utf16, 67)
+        );
+    }
+}
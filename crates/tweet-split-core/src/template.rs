@@ -0,0 +1,649 @@
+// templates rendered around each tweet, like `{{index}}/{{total}} ` or `Day {{index+3}}: `
+//
+// placeholders are written as `{{expression}}` or `{expression}`,
+// optionally followed by `:format`.
+// expressions are integer arithmetic (`+ - * /`) over
+// `index` (1-based), `total`, and integer literals.
+// the special `today` helper takes a strftime-style format,
+// e.g. `{{today:%Y-%m-%d}}`.
+// a literal brace is written as `\{` or `\}`.
+
+use crate::count::{CountMode, Counter};
+use crate::TweetSplitError;
+use std::fmt::Write;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+// the widest a number can be padded to, like `{{index:03}}`,
+// which is already wider than any tweet needs
+const MAX_WIDTH: usize = 64;
+
+/// A proleptic Gregorian calendar date, used by the `today` helper.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// The date with 1-based `month` and `day`, if there is one.
+    pub fn new(year: i64, month: u32, day: u32) -> Result<Self, TweetSplitError> {
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days_in_month = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => 0,
+        };
+
+        if day == 0 || day > days_in_month {
+            return Err(invalid(format!(
+                "{}-{:02}-{:02} is not a date.",
+                year, month, day
+            )));
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> i64 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// The number of days between 1970-01-01 and this date.
+    pub fn days_since_epoch(&self) -> i64 {
+        let month = i64::from(self.month);
+        let year = if month <= 2 { self.year - 1 } else { self.year };
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let year_of_era = year - era * 400;
+        let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+        let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    // 0 is Sunday
+    fn weekday(&self) -> usize {
+        (self.days_since_epoch() + 4).rem_euclid(7) as usize
+    }
+
+    fn day_of_year(&self) -> i64 {
+        let new_years_day = Date {
+            year: self.year,
+            month: 1,
+            day: 1,
+        };
+
+        self.days_since_epoch() - new_years_day.days_since_epoch() + 1
+    }
+
+    /// Format the date with a subset of strftime:
+    /// `%Y %y %m %d %e %j %B %b %A %a %%`.
+    pub fn format(&self, format: &str) -> Result<String, TweetSplitError> {
+        let mut output = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => output.push_str(&self.year.to_string()),
+                Some('y') => output.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => output.push_str(&format!("{:02}", self.month)),
+                Some('d') => output.push_str(&format!("{:02}", self.day)),
+                Some('e') => output.push_str(&format!("{:>2}", self.day)),
+                Some('j') => output.push_str(&format!("{:03}", self.day_of_year())),
+                Some('B') => output.push_str(MONTH_NAMES[self.month as usize - 1]),
+                Some('b') => output.push_str(&MONTH_NAMES[self.month as usize - 1][..3]),
+                Some('A') => output.push_str(WEEKDAY_NAMES[self.weekday()]),
+                Some('a') => output.push_str(&WEEKDAY_NAMES[self.weekday()][..3]),
+                Some('%') => output.push('%'),
+                Some(other) => {
+                    return Err(invalid(format!(
+                        "Unsupported date format specifier `%{}`.",
+                        other
+                    )))
+                }
+                None => return Err(invalid("Date format ends with a lone `%`.".to_string())),
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// The values a template can refer to when rendered for one tweet.
+#[derive(Clone, Copy, Debug)]
+pub struct TemplateContext {
+    /// 1-based position of the tweet in the thread
+    pub index: usize,
+    pub total: usize,
+    pub today: Option<Date>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Number {
+        expression: Expression,
+        width: Option<(usize, char)>,
+    },
+    Today {
+        format: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    Index,
+    Total,
+    Integer(i64),
+}
+
+// operands combined left to right, with `*` and `/` binding tighter than `+` and `-`
+#[derive(Clone, Debug, PartialEq)]
+struct Expression {
+    first: Operand,
+    rest: Vec<(char, Operand)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TweetSplitError> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut rest = source;
+
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '\\' if rest[1..].starts_with(['{', '}']) => {
+                    literal.push_str(&rest[1..2]);
+                    rest = &rest[2..];
+                }
+                '{' => {
+                    let (open, close) = if rest.starts_with("{{") {
+                        ("{{", "}}")
+                    } else {
+                        ("{", "}")
+                    };
+
+                    let body = &rest[open.len()..];
+                    let end = body.find(close).ok_or_else(|| {
+                        invalid(format!("Unterminated placeholder in `{}`.", source))
+                    })?;
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_placeholder(&body[..end])?);
+
+                    rest = &body[end + close.len()..];
+                }
+                '}' => {
+                    return Err(invalid(format!("Unmatched `}}` in `{}`.", source)));
+                }
+                _ => {
+                    literal.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether the rendered text can change with the total number of tweets.
+    pub fn depends_on_total(&self) -> bool {
+        self.segments.iter().any(|segment| match segment {
+            Segment::Number { expression, .. } => {
+                expression.operands().any(|o| o == &Operand::Total)
+            }
+            _ => false,
+        })
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> Result<String, TweetSplitError> {
+        let mut output = String::new();
+        self.render_into(context, &mut output)?;
+
+        Ok(output)
+    }
+
+    /// Append the rendered template to `output`.
+    pub fn render_into(
+        &self,
+        context: &TemplateContext,
+        output: &mut String,
+    ) -> Result<(), TweetSplitError> {
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => output.push_str(literal),
+                Segment::Number { expression, width } => {
+                    let value = expression.evaluate(context)?;
+                    // writing to a String cannot fail
+                    let _ = match width {
+                        Some((width, '0')) => write!(output, "{:0w$}", value, w = width),
+                        Some((width, _)) => write!(output, "{:>w$}", value, w = width),
+                        None => write!(output, "{}", value),
+                    };
+                }
+                Segment::Today { format } => {
+                    output.push_str(&today(context)?.format(format)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The length of the rendered template counted with `count`, without rendering numbers.
+    pub fn rendered_len(
+        &self,
+        context: &TemplateContext,
+        count: CountMode,
+    ) -> Result<usize, TweetSplitError> {
+        let mut len = 0;
+
+        for segment in &self.segments {
+            len += match segment {
+                Segment::Literal(literal) => count.count(literal),
+                Segment::Number { expression, width } => {
+                    let digits = decimal_len(expression.evaluate(context)?);
+                    width.map_or(digits, |(width, _)| digits.max(width))
+                }
+                Segment::Today { format } => count.count(&today(context)?.format(format)?),
+            };
+        }
+
+        Ok(len)
+    }
+}
+
+/// A template for the whole of each tweet, like `{index}/{total} {text} 🧵`,
+/// which is a prefix and a suffix around the `{text}` placeholder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TweetTemplate {
+    pub prefix: Template,
+    pub suffix: Template,
+}
+
+impl TweetTemplate {
+    pub fn parse(source: &str) -> Result<Self, TweetSplitError> {
+        let found = |placeholder| {
+            source
+                .match_indices(placeholder)
+                .filter(|(start, _)| !source[..*start].ends_with('\\'))
+                .collect::<Vec<(usize, &str)>>()
+        };
+        let mut placeholders = found("{{text}}");
+        if placeholders.is_empty() {
+            placeholders = found("{text}");
+        }
+
+        match placeholders[..] {
+            [(start, placeholder)] => Ok(Self {
+                prefix: Template::parse(&source[..start])?,
+                suffix: Template::parse(&source[start + placeholder.len()..])?,
+            }),
+            [] => Err(invalid(format!(
+                "The tweet template `{}` has no `{{text}}`.",
+                source
+            ))),
+            _ => Err(invalid(format!(
+                "The tweet template `{}` has more than one `{{text}}`.",
+                source
+            ))),
+        }
+    }
+}
+
+fn today(context: &TemplateContext) -> Result<Date, TweetSplitError> {
+    context
+        .today
+        .ok_or_else(|| invalid("The `today` helper needs the current date.".to_string()))
+}
+
+// the length of `value` written in decimal, including any minus sign
+pub(crate) fn decimal_len(value: i64) -> usize {
+    let sign = if value < 0 { 1 } else { 0 };
+    let mut magnitude = value.unsigned_abs();
+    let mut digits = 1;
+
+    while magnitude >= 10 {
+        magnitude /= 10;
+        digits += 1;
+    }
+
+    sign + digits
+}
+
+impl Expression {
+    fn operands(&self) -> impl Iterator<Item = &Operand> {
+        std::iter::once(&self.first).chain(self.rest.iter().map(|(_, operand)| operand))
+    }
+
+    fn evaluate(&self, context: &TemplateContext) -> Result<i64, TweetSplitError> {
+        let value_of = |operand: &Operand| match operand {
+            Operand::Index => context.index as i64,
+            Operand::Total => context.total as i64,
+            Operand::Integer(integer) => *integer,
+        };
+
+        let overflow = || invalid("Arithmetic overflow in template.".to_string());
+
+        // a running sum of products, so `*` and `/` bind tighter than `+` and `-`
+        let mut sum = 0i64;
+        let mut sign = 1i64;
+        let mut product = value_of(&self.first);
+
+        for (operator, operand) in &self.rest {
+            let value = value_of(operand);
+            match operator {
+                '*' => product = product.checked_mul(value).ok_or_else(overflow)?,
+                '/' => {
+                    if value == 0 {
+                        return Err(invalid("Division by zero in template.".to_string()));
+                    }
+                    product = product.checked_div(value).ok_or_else(overflow)?
+                }
+                '+' | '-' => {
+                    sum = sign
+                        .checked_mul(product)
+                        .and_then(|term| sum.checked_add(term))
+                        .ok_or_else(overflow)?;
+                    sign = if *operator == '+' { 1 } else { -1 };
+                    product = value;
+                }
+                _ => unreachable!("operators are validated when parsing"),
+            }
+        }
+
+        sign.checked_mul(product)
+            .and_then(|term| sum.checked_add(term))
+            .ok_or_else(overflow)
+    }
+}
+
+fn parse_placeholder(body: &str) -> Result<Segment, TweetSplitError> {
+    let (expression, format) = match body.find(':') {
+        Some(colon) => (body[..colon].trim(), Some(&body[colon + 1..])),
+        None => (body.trim(), None),
+    };
+
+    if expression == "today" {
+        let format = format.unwrap_or("%Y-%m-%d").to_string();
+        // surface bad specifiers when the template is parsed rather than rendered
+        Date::from_days_since_epoch(0).format(&format)?;
+        return Ok(Segment::Today { format });
+    }
+
+    let width = match format {
+        Some(format) => {
+            let width = format
+                .parse::<usize>()
+                .map_err(|_| invalid(format!("Invalid number format `{}`.", format)))?;
+            if width > MAX_WIDTH {
+                return Err(invalid(format!(
+                    "Number width {} is wider than the most allowed, {}.",
+                    width, MAX_WIDTH
+                )));
+            }
+            let fill = if format.starts_with('0') { '0' } else { ' ' };
+            Some((width, fill))
+        }
+        None => None,
+    };
+
+    Ok(Segment::Number {
+        expression: parse_expression(expression)?,
+        width,
+    })
+}
+
+fn parse_expression(source: &str) -> Result<Expression, TweetSplitError> {
+    let mut operands = vec![];
+    let mut operators = vec![];
+    let mut token = String::new();
+
+    for c in source.chars().chain(std::iter::once('\n')) {
+        match c {
+            '+' | '-' | '*' | '/' | '\n' => {
+                operands.push(parse_operand(token.trim(), source)?);
+                token.clear();
+                if c != '\n' {
+                    operators.push(c);
+                }
+            }
+            _ => token.push(c),
+        }
+    }
+
+    let mut operands = operands.into_iter();
+    let first = operands
+        .next()
+        .expect("there is always at least one operand");
+
+    Ok(Expression {
+        first,
+        rest: operators.into_iter().zip(operands).collect(),
+    })
+}
+
+fn parse_operand(token: &str, source: &str) -> Result<Operand, TweetSplitError> {
+    match token {
+        "index" => Ok(Operand::Index),
+        "total" => Ok(Operand::Total),
+        _ => token
+            .parse::<i64>()
+            .map(Operand::Integer)
+            .map_err(|_| invalid(format!("Invalid template expression `{}`.", source))),
+    }
+}
+
+fn invalid(details: String) -> TweetSplitError {
+    TweetSplitError::InvalidTemplate { details }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(index: usize, total: usize) -> TemplateContext {
+        TemplateContext {
+            index,
+            total,
+            today: Some(Date::new(2020, 2, 29).unwrap()),
+        }
+    }
+
+    #[test]
+    fn it_splits_tweet_templates_around_the_text() {
+        let template = TweetTemplate::parse("{index}/{total} {text} 🧵").unwrap();
+
+        assert_eq!(template.prefix.render(&context(2, 5)).unwrap(), "2/5 ");
+        assert_eq!(template.suffix.render(&context(2, 5)).unwrap(), " 🧵");
+        assert_eq!(
+            TweetTemplate::parse("{{text}} ({{today:%b %e}})")
+                .unwrap()
+                .suffix
+                .render(&context(1, 1))
+                .unwrap(),
+            " (Feb 29)"
+        );
+        assert!(TweetTemplate::parse("{index}/{total}").is_err());
+        assert!(TweetTemplate::parse("{text} and {text}").is_err());
+        assert!(TweetTemplate::parse("\\{text} alone").is_err());
+    }
+
+    #[test]
+    fn it_renders_index_and_total() {
+        let template = Template::parse("({{index}} of {total}) ").unwrap();
+
+        assert_eq!(template.render(&context(3, 12)).unwrap(), "(3 of 12) ");
+    }
+
+    #[test]
+    fn it_renders_arithmetic() {
+        let template =
+            Template::parse("Day {{index + 3}}: {{total - index}} left, {{index*2+1}}").unwrap();
+
+        assert_eq!(template.render(&context(1, 5)).unwrap(), "Day 4: 4 left, 3");
+    }
+
+    #[test]
+    fn it_pads_numbers() {
+        let template = Template::parse("{{index:03}}|{{total:3}}").unwrap();
+
+        assert_eq!(template.render(&context(7, 12)).unwrap(), "007| 12");
+    }
+
+    #[test]
+    fn it_renders_dates() {
+        let template =
+            Template::parse("{{today:%Y-%m-%d}} {{today:%a %e %b, day %j}} \\{x\\}").unwrap();
+
+        assert_eq!(
+            template.render(&context(1, 1)).unwrap(),
+            "2020-02-29 Sat 29 Feb, day 060 {x}"
+        );
+    }
+
+    #[test]
+    fn it_measures_without_rendering() {
+        let template =
+            Template::parse("{{index:03}}|{{total:3}} {{index - 20}} {{today:%B}} ").unwrap();
+
+        for index in 1..=1200 {
+            let context = context(index, 1200);
+
+            assert_eq!(
+                template.rendered_len(&context, CountMode::Bytes).unwrap(),
+                template.render(&context).unwrap().len()
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_templates() {
+        assert!(Template::parse("{{index").is_err());
+        assert!(Template::parse("{{bogus}}").is_err());
+        assert!(Template::parse("{{today:%Q}}").is_err());
+        assert!(Template::parse("oops}").is_err());
+        assert!(Template::parse("{{index:999999999}}").is_err());
+    }
+
+    #[test]
+    fn it_rejects_arithmetic_that_overflows() {
+        for source in [
+            "{{index * 9223372036854775807}}",
+            "{{total + 9223372036854775807}}",
+            "{{0 - 9223372036854775807 - 2}}",
+        ] {
+            assert!(matches!(
+                Template::parse(source).unwrap().render(&context(2, 5)),
+                Err(TweetSplitError::InvalidTemplate { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn it_knows_whether_it_depends_on_total() {
+        assert!(Template::parse("{index}/{total}")
+            .unwrap()
+            .depends_on_total());
+        assert!(!Template::parse("{index}. ").unwrap().depends_on_total());
+    }
+
+    #[test]
+    fn it_converts_days_to_dates_and_back() {
+        for days in -800_000..800_000 {
+            if days % 997 == 0 {
+                assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+            }
+        }
+
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date::new(1970, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_only_makes_real_dates() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2023, 2, 29).is_err());
+        assert!(Date::new(1900, 2, 29).is_err());
+        assert!(Date::new(2026, 0, 1).is_err());
+        assert!(Date::new(2026, 13, 1).is_err());
+        assert!(Date::new(2026, 4, 31).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+// a split thread, keeping each tweet's text apart from its decorations
+
+use crate::count::{CountMode, Counter};
+use crate::entities::{self, Entity};
+use crate::id;
+use crate::wrap;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thread {
+    /// Each tweet's slice of the input, without prefixes, suffixes, counters, or markers
+    pub texts: Vec<String>,
+    /// Each tweet as it should be posted
+    pub tweets: Vec<String>,
+    /// The byte range of each tweet's text in the input
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// One tweet of a thread with what editors and bots otherwise work out again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tweet {
+    /// The tweet as it should be posted
+    pub text: String,
+    /// 0-based position of the tweet in the thread
+    pub index: usize,
+    pub total: usize,
+    /// The length of `text`, counted like `max_tweet_length`
+    pub length: usize,
+    /// The byte range of the tweet's text in the input, without its decorations
+    pub range: Range<usize>,
+}
+
+impl Thread {
+    /// A SHA-256 of the thread's texts in order, as 64 hex digits.
+    ///
+    /// Decorations and whitespace differences within a tweet don't change the hash,
+    /// but moving a word into another tweet does.
+    pub fn content_hash(&self) -> String {
+        let mut message = String::new();
+
+        for text in &self.texts {
+            message.push_str(&id::normalize(text));
+            message.push('\n');
+        }
+
+        id::sha256(message.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Each tweet with its position, its length counted with `count`, and its place in the input.
+    pub fn tweets_counted(&self, count: CountMode) -> Vec<Tweet> {
+        let total = self.tweets.len();
+
+        self.tweets
+            .iter()
+            .zip(&self.ranges)
+            .enumerate()
+            .map(|(index, (tweet, range))| Tweet {
+                text: tweet.clone(),
+                index,
+                total,
+                length: count.count(tweet),
+                range: range.clone(),
+            })
+            .collect()
+    }
+
+    /// The links, mentions, hashtags, and cashtags of each tweet as posted,
+    /// with ranges counted with `count`.
+    pub fn entities(&self, count: CountMode) -> Vec<Vec<Entity>> {
+        self.tweets
+            .iter()
+            .map(|tweet| entities::extract(tweet, count))
+            .collect()
+    }
+
+    /// How full each tweet is, with lengths counted with `count`
+    /// against a limit of `max_tweet_length`.
+    pub fn stats(&self, count: CountMode, max_tweet_length: usize) -> Stats {
+        Stats {
+            lengths: self.tweets.iter().map(|tweet| count.count(tweet)).collect(),
+            max_tweet_length,
+        }
+    }
+
+    /// The thread as `ts` previews it in a terminal, with lengths counted with `count`.
+    pub fn preview(&self, count: CountMode) -> Preview<'_> {
+        Preview {
+            thread: self,
+            count,
+            ids: false,
+            only: None,
+        }
+    }
+}
+
+/// The length of each tweet of a thread, and how much of the limit they use.
+///
+/// Displays as a table of each tweet's length and share of the limit,
+/// followed by the totals and the least, mean, and most used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub lengths: Vec<usize>,
+    pub max_tweet_length: usize,
+}
+
+impl Stats {
+    /// The lengths of all the tweets together.
+    pub fn total_length(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+
+    /// The share of the limit used by the tweet at 0-based `index`, from 0 to 1.
+    pub fn utilization(&self, index: usize) -> f64 {
+        if self.max_tweet_length == 0 {
+            return 0.0;
+        }
+
+        self.lengths[index] as f64 / self.max_tweet_length as f64
+    }
+
+    /// The least, mean, and most utilization of any tweet, or all 0 for no tweets.
+    pub fn utilization_range(&self) -> (f64, f64, f64) {
+        if self.lengths.is_empty() {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let utilizations = (0..self.lengths.len())
+            .map(|index| self.utilization(index))
+            .collect::<Vec<f64>>();
+        let min = utilizations.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = utilizations.iter().cloned().fold(0.0, f64::max);
+        let mean = utilizations.iter().sum::<f64>() / utilizations.len() as f64;
+
+        (min, mean, max)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = |utilization: f64| (utilization * 100.0).round() as usize;
+
+        writeln!(f, "tweet  length  used")?;
+        for (index, length) in self.lengths.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5}  {:>6}  {:>3}%",
+                index + 1,
+                length,
+                percent(self.utilization(index))
+            )?;
+        }
+
+        let (min, mean, max) = self.utilization_range();
+        writeln!(
+            f,
+            "{} tweets, {} long in all, of {} each",
+            self.lengths.len(),
+            self.total_length(),
+            self.max_tweet_length
+        )?;
+        writeln!(
+            f,
+            "used: least {}%, mean {}%, most {}%",
+            percent(min),
+            percent(mean),
+            percent(max)
+        )
+    }
+}
+
+/// Each tweet of a thread under a header like `--- 1/3 (length 42) ---`,
+/// re-wrapped to the formatter's width when there is one, as in `format!("{:60}", preview)`.
+#[derive(Clone, Copy, Debug)]
+pub struct Preview<'a> {
+    thread: &'a Thread,
+    count: CountMode,
+    ids: bool,
+    only: Option<usize>,
+}
+
+impl Preview<'_> {
+    /// The preview with each tweet's id in its header, as in `--- 1/3 (length 42, id 14c7f8774631c01d) ---`.
+    pub fn with_ids(mut self) -> Self {
+        self.ids = true;
+        self
+    }
+
+    /// Only the tweet at 0-based `index`, as it appears in the whole preview,
+    /// so that something can be printed after each tweet.
+    pub fn only(mut self, index: usize) -> Self {
+        self.only = Some(index);
+        self
+    }
+}
+
+impl fmt::Display for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.thread.tweets.len();
+
+        for (index, tweet) in self.thread.tweets.iter().enumerate() {
+            if self.only.is_some_and(|only| only != index) {
+                continue;
+            }
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "--- {}/{} (length {}",
+                index + 1,
+                total,
+                self.count.count(tweet)
+            )?;
+            if self.ids {
+                write!(f, ", id {}", id::tweet_id(tweet, index + 1))?;
+            }
+            writeln!(f, ") ---")?;
+            match f.width() {
+                Some(width) => writeln!(f, "{}", wrap::wrap(tweet, width))?,
+                None => writeln!(f, "{}", tweet)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The preview of the thread with lengths in bytes; see `Thread::preview`.
+impl fmt::Display for Thread {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.preview(CountMode::default()).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tweet;
+    use crate::count::CountMode;
+    use crate::numbering::{Numbering, NumberingPosition, NumberingStyle};
+    use crate::{split_thread, SplitOptions};
+
+    const TEXT: &str = "one two three four five six seven eight nine ten";
+
+    #[test]
+    fn it_ignores_decorations() {
+        let numbered = |style, position| {
+            let mut options = SplitOptions::new(20);
+            options.numbering = Some(Numbering::new(style, position));
+            split_thread(TEXT, &options).unwrap()
+        };
+
+        let slash = numbered(NumberingStyle::Slash, NumberingPosition::Suffix);
+        let letters = numbered(NumberingStyle::Letters, NumberingPosition::Prefix);
+
+        assert_ne!(slash.tweets, letters.tweets);
+        assert_eq!(slash.texts, letters.texts);
+        assert_eq!(slash.content_hash(), letters.content_hash());
+        assert_eq!(slash.content_hash().len(), 64);
+    }
+
+    #[test]
+    fn it_previews_each_tweet_under_a_header() {
+        let thread = split_thread(TEXT, &SplitOptions::new(30)).unwrap();
+
+        assert_eq!(
+            thread.to_string(),
+            "--- 1/2 (length 27) ---\none two three four five six\n\n\
+             --- 2/2 (length 20) ---\nseven eight nine ten\n"
+        );
+        assert!(
+            format!("{:12}", thread).starts_with("--- 1/2 (length 27) ---\none two\nthree four\n")
+        );
+
+        let second = thread.preview(CountMode::Bytes).with_ids().only(1);
+        assert_eq!(
+            second.to_string(),
+            format!(
+                "\n--- 2/2 (length 20, id {}) ---\nseven eight nine ten\n",
+                crate::id::tweet_id("seven eight nine ten", 2)
+            )
+        );
+    }
+
+    #[test]
+    fn it_places_each_tweet_in_the_input() {
+        let input = format!("  {}\n", TEXT);
+        let mut options = SplitOptions::new(20);
+        options.numbering = Some(Numbering::new(
+            NumberingStyle::Slash,
+            NumberingPosition::Suffix,
+        ));
+        let tweets = crate::split_tweets(&input, &options).unwrap();
+
+        assert_eq!(tweets.len(), 4);
+        assert_eq!(
+            tweets[1],
+            Tweet {
+                text: "four five six 2/4".to_string(),
+                index: 1,
+                total: 4,
+                length: 17,
+                range: 16..29,
+            }
+        );
+        assert_eq!(&input[tweets[1].range.clone()], "four five six");
+    }
+
+    #[test]
+    fn it_changes_when_tweets_change() {
+        let wide = split_thread(TEXT, &SplitOptions::new(30)).unwrap();
+        let narrow = split_thread(TEXT, &SplitOptions::new(20)).unwrap();
+        let edited = split_thread(&TEXT.replace("six", "6"), &SplitOptions::new(30)).unwrap();
+
+        assert_ne!(wide.content_hash(), narrow.content_hash());
+        assert_ne!(wide.content_hash(), edited.content_hash());
+    }
+
+    #[test]
+    fn it_reports_how_full_each_tweet_is() {
+        let thread = split_thread(TEXT, &SplitOptions::new(20)).unwrap();
+        let stats = thread.stats(CountMode::Bytes, 20);
+
+        assert_eq!(stats.lengths, vec![18, 20, 8]);
+        assert_eq!(stats.total_length(), 46);
+        assert_eq!(
+            stats.to_string(),
+            "tweet  length  used\n    1      18   90%\n    2      20  100%\n    3       8   40%\n\
+             3 tweets, 46 long in all, of 20 each\nused: least 40%, mean 77%, most 100%\n"
+        );
+    }
+}
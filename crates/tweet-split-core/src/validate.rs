@@ -0,0 +1,312 @@
+// platform rules beyond length that a tweet can break
+
+use crate::count::{CountMode, Counter};
+use crate::entities::{self, EntityKind};
+use crate::id;
+use crate::platform::Platform;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// What a single post may contain, besides fitting the length limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub max_mentions: Option<usize>,
+    pub max_hashtags: Option<usize>,
+    pub max_urls: Option<usize>,
+    /// Characters that make a post invalid wherever they appear
+    pub forbidden: Vec<char>,
+    /// Whether a thread may not repeat a post, ignoring case and whitespace
+    pub forbid_duplicates: bool,
+}
+
+impl Rules {
+    /// The rules ts checks for `platform`.
+    ///
+    /// The forbidden characters are the ones the platform rejects.
+    /// Neither platform publishes limits on mentions, hashtags, or links,
+    /// so these are conservative ones past which posts tend to be refused as spam.
+    /// Twitter refuses a post that repeats a recent one.
+    pub fn for_platform(platform: Platform) -> Self {
+        // twitter-text's invalid characters: noncharacters, the byte order mark,
+        // and the directional formatting characters
+        let mut forbidden = vec!['\u{FFFE}', '\u{FEFF}', '\u{FFFF}'];
+        forbidden.extend('\u{202A}'..='\u{202E}');
+
+        match platform {
+            Platform::Twitter => Self {
+                max_mentions: Some(10),
+                max_hashtags: Some(10),
+                max_urls: Some(5),
+                forbidden,
+                forbid_duplicates: true,
+            },
+            Platform::Bluesky => {
+                forbidden.push('\0');
+
+                Self {
+                    max_mentions: Some(10),
+                    max_hashtags: Some(8),
+                    max_urls: Some(5),
+                    forbidden,
+                    forbid_duplicates: false,
+                }
+            }
+        }
+    }
+}
+
+/// A way a tweet breaks its platform's rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    TooManyMentions {
+        count: usize,
+        max: usize,
+    },
+    TooManyHashtags {
+        count: usize,
+        max: usize,
+    },
+    TooManyUrls {
+        count: usize,
+        max: usize,
+    },
+    ForbiddenCharacter {
+        character: char,
+        /// The byte offset of the character in the tweet
+        offset: usize,
+    },
+    Duplicate {
+        /// The 0-based index of the earlier tweet with the same text
+        of: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::TooManyMentions { count, max } => {
+                write!(f, "{} mentions, more than the {} allowed", count, max)
+            }
+            Violation::TooManyHashtags { count, max } => {
+                write!(f, "{} hashtags, more than the {} allowed", count, max)
+            }
+            Violation::TooManyUrls { count, max } => {
+                write!(f, "{} links, more than the {} allowed", count, max)
+            }
+            Violation::ForbiddenCharacter { character, offset } => write!(
+                f,
+                "the forbidden character U+{:04X} at byte {}",
+                *character as u32, offset
+            ),
+            Violation::Duplicate { of } => write!(f, "the same text as tweet {}", of + 1),
+        }
+    }
+}
+
+/// Every rule `tweet` breaks.
+pub fn check(tweet: &str, rules: &Rules) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    let entities = entities::extract(tweet, CountMode::Bytes);
+    let limits = [
+        (rules.max_mentions, EntityKind::Mention),
+        (rules.max_hashtags, EntityKind::Hashtag),
+        (rules.max_urls, EntityKind::Url),
+    ];
+    for (max, kind) in limits.iter() {
+        let count = entities
+            .iter()
+            .filter(|entity| entity.kind == *kind)
+            .count();
+
+        match max {
+            Some(max) if count > *max => violations.push(match kind {
+                EntityKind::Mention => Violation::TooManyMentions { count, max: *max },
+                EntityKind::Hashtag => Violation::TooManyHashtags { count, max: *max },
+                _ => Violation::TooManyUrls { count, max: *max },
+            }),
+            _ => (),
+        }
+    }
+
+    for (offset, character) in tweet.char_indices() {
+        if rules.forbidden.contains(&character) {
+            violations.push(Violation::ForbiddenCharacter { character, offset });
+        }
+    }
+
+    violations
+}
+
+/// Every rule broken by each tweet of a thread, with the tweet's 0-based index.
+pub fn check_thread(tweets: &[String], rules: &Rules) -> Vec<(usize, Violation)> {
+    let mut violations = tweets
+        .iter()
+        .enumerate()
+        .flat_map(|(index, tweet)| {
+            check(tweet, rules)
+                .into_iter()
+                .map(move |violation| (index, violation))
+        })
+        .collect::<Vec<(usize, Violation)>>();
+
+    if rules.forbid_duplicates {
+        violations.extend(
+            duplicates(tweets)
+                .into_iter()
+                .map(|(index, of)| (index, Violation::Duplicate { of })),
+        );
+        violations.sort_by_key(|(index, _)| *index);
+    }
+
+    violations
+}
+
+/// Each tweet that repeats an earlier one, ignoring case and whitespace,
+/// as its 0-based index and the index of the first tweet it repeats.
+pub fn duplicates(tweets: &[String]) -> Vec<(usize, usize)> {
+    let mut first = HashMap::new();
+
+    tweets
+        .iter()
+        .enumerate()
+        .filter_map(|(index, tweet)| {
+            let of = *first.entry(duplicate_key(tweet)).or_insert(index);
+            Some((index, of)).filter(|_| of != index)
+        })
+        .collect()
+}
+
+/// Make each tweet that repeats an earlier one differ from it by appending zero-width spaces,
+/// as long as they fit in `max_tweet_length` measured with `count`.
+///
+/// Readers won't see the difference, but a platform that refuses duplicates will.
+pub fn vary_duplicates(tweets: &mut [String], max_tweet_length: usize, count: CountMode) {
+    let mut seen = HashSet::new();
+
+    for tweet in tweets.iter_mut() {
+        let mut varied = tweet.clone();
+        while seen.contains(&duplicate_key(&varied)) {
+            varied.push(VARIATION);
+            if count.count(&varied) > max_tweet_length {
+                varied = tweet.clone();
+                break;
+            }
+        }
+
+        seen.insert(duplicate_key(&varied));
+        *tweet = varied;
+    }
+}
+
+const VARIATION: char = '\u{200B}';
+
+// tweets with the same key are the same post to a reader
+fn duplicate_key(tweet: &str) -> String {
+    id::normalize(tweet).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Rules {
+        Rules {
+            max_mentions: Some(2),
+            max_hashtags: Some(1),
+            max_urls: Some(1),
+            forbidden: vec!['\u{FEFF}'],
+            forbid_duplicates: true,
+        }
+    }
+
+    #[test]
+    fn it_accepts_tweets_within_the_rules() {
+        let tweet = "thanks @alice and @bob.bsky.social for #rustlang, see https://example.com";
+
+        assert_eq!(check(tweet, &rules()), vec![]);
+    }
+
+    #[test]
+    fn it_counts_mentions_hashtags_and_links() {
+        let tweet = "@a @b @c #one #two https://a.example www.b.example";
+
+        assert_eq!(
+            check(tweet, &rules()),
+            vec![
+                Violation::TooManyMentions { count: 3, max: 2 },
+                Violation::TooManyHashtags { count: 2, max: 1 },
+                Violation::TooManyUrls { count: 2, max: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_ignores_emails_numbers_and_entities() {
+        let tweet = "mail me@example.com about issue #42 &#39;s fix";
+
+        assert_eq!(
+            check(tweet, &Rules::for_platform(Platform::Twitter)),
+            vec![]
+        );
+        assert_eq!(entities::extract(tweet, CountMode::Bytes), vec![]);
+    }
+
+    #[test]
+    fn it_finds_forbidden_characters() {
+        let violations = check_thread(
+            &["fine".to_string(), "bom \u{FEFF}here".to_string()],
+            &rules(),
+        );
+
+        assert_eq!(
+            violations,
+            vec![(
+                1,
+                Violation::ForbiddenCharacter {
+                    character: '\u{FEFF}',
+                    offset: 4
+                }
+            )]
+        );
+        assert_eq!(
+            violations[0].1.to_string(),
+            "the forbidden character U+FEFF at byte 4"
+        );
+    }
+
+    #[test]
+    fn it_finds_duplicate_tweets() {
+        let tweets = ["Same", "other", "same ", "SAME"].map(String::from);
+
+        assert_eq!(duplicates(&tweets), vec![(2, 0), (3, 0)]);
+        assert_eq!(
+            check_thread(&tweets, &rules()),
+            vec![
+                (2, Violation::Duplicate { of: 0 }),
+                (3, Violation::Duplicate { of: 0 })
+            ]
+        );
+        assert_eq!(
+            check_thread(&tweets, &Rules::for_platform(Platform::Bluesky)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn it_varies_duplicates_that_have_room() {
+        let mut tweets = ["again", "again", "again", "again!"].map(String::from);
+
+        vary_duplicates(&mut tweets, 7, CountMode::Graphemes);
+
+        assert_eq!(
+            tweets,
+            ["again", "again\u{200B}", "again\u{200B}\u{200B}", "again!"]
+        );
+        assert_eq!(duplicates(&tweets), vec![]);
+
+        let mut tweets = ["again", "again"].map(String::from);
+        vary_duplicates(&mut tweets, 5, CountMode::Graphemes);
+        assert_eq!(duplicates(&tweets), vec![(1, 0)]);
+    }
+}
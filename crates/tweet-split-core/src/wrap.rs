@@ -0,0 +1,73 @@
+// re-wrapping tweets for previews, by display width,
+// so wide characters like emoji and CJK take the two columns they're shown in
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// `text` with each line wrapped to at most `width` columns, breaking at spaces when possible.
+pub fn wrap(text: &str, width: usize) -> String {
+    let width = width.max(1);
+
+    text.split('\n')
+        .map(|line| wrap_line(line, width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn wrap_line(line: &str, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+
+    for word in line.split(' ') {
+        let word_width = word.width();
+
+        if column > 0 && column + 1 + word_width <= width {
+            output.push(' ');
+            column += 1;
+        } else if column > 0 {
+            output.push('\n');
+            column = 0;
+        }
+
+        // a word wider than a whole line breaks wherever it has to
+        for c in word.chars() {
+            let char_width = c.width().unwrap_or(0);
+
+            if column > 0 && column + char_width > width {
+                output.push('\n');
+                column = 0;
+            }
+
+            output.push(c);
+            column += char_width;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_wraps_at_spaces() {
+        assert_eq!(
+            wrap("the quick brown fox\njumps", 10),
+            "the quick\nbrown fox\njumps"
+        );
+    }
+
+    #[test]
+    fn it_counts_display_width() {
+        assert_eq!(wrap("日本語 テキスト", 8), "日本語\nテキスト");
+        assert_eq!(wrap("🧵🧵🧵", 4), "🧵🧵\n🧵");
+    }
+
+    #[test]
+    fn it_breaks_words_wider_than_a_line() {
+        assert_eq!(
+            wrap("a https://example.com", 8),
+            "a\nhttps://\nexample.\ncom"
+        );
+    }
+}
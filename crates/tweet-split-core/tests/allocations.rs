@@ -0,0 +1,98 @@
+// counts heap allocations made while splitting, to keep them from creeping up per tweet
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use tweet_split_core::count::{CountMode, Counter};
+use tweet_split_core::numbering::{Numbering, NumberingPosition, NumberingStyle};
+use tweet_split_core::template::Template;
+use tweet_split_core::{split_with_options, SplitOptions};
+
+struct CountingAllocator;
+
+thread_local! {
+    // per thread, so tests running in parallel don't count each other's allocations
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const TRAITOROUS_EIGHT: &str = "The traitorous eight was a group of eight employees who left Shockley Semiconductor Laboratory in 1957 to found Fairchild Semiconductor. William Shockley had in 1956 recruited a group of young PhD graduates with the goal to develop and produce new semiconductor devices. While Shockley had received a Nobel Prize in Physics and was an experienced researcher and teacher, his management of the group was authoritarian and unpopular. This was accentuated by Shockley's research focus not proving fruitful. After the demand for Shockley to be replaced was rebuffed, the eight left to form their own company.";
+
+// the allocations made by the second call to `split`, and the number of tweets it made.
+// the first call on a thread also compiles lazily built regexes and sets up their caches
+fn count_allocations(split: impl Fn() -> Vec<String>) -> (usize, usize) {
+    split();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let tweets = split().len();
+    let after = ALLOCATIONS.with(Cell::get);
+
+    (after - before, tweets)
+}
+
+#[test]
+fn it_allocates_little_per_plain_tweet() {
+    let options = SplitOptions::new(40);
+
+    let (allocations, tweets) =
+        count_allocations(|| split_with_options(TRAITOROUS_EIGHT, &options).unwrap());
+
+    println!("{} allocations for {} tweets", allocations, tweets);
+    assert!(allocations <= 2 * tweets + 24);
+}
+
+#[test]
+fn it_allocates_little_per_decorated_tweet() {
+    let mut options = SplitOptions::new(40);
+    options.prefix = Some(Template::parse("{{index}}. ").unwrap());
+    options.numbering = Some(Numbering::new(
+        NumberingStyle::Slash,
+        NumberingPosition::Suffix,
+    ));
+    options.end_marker = Some("/end".to_string());
+
+    let (allocations, tweets) =
+        count_allocations(|| split_with_options(TRAITOROUS_EIGHT, &options).unwrap());
+
+    println!("{} allocations for {} tweets", allocations, tweets);
+    assert!(allocations <= 3 * tweets + 24);
+}
+
+#[test]
+fn it_counts_without_allocating() {
+    let text = "ok 👍🏽 see https://example.com/a-long-link 日本語 👩\u{200D}👩\u{200D}👧 café";
+    let modes = [
+        CountMode::Bytes,
+        CountMode::Utf16,
+        CountMode::Graphemes,
+        CountMode::TwitterWeighted,
+        CountMode::Mastodon,
+        CountMode::Gsm7,
+    ];
+
+    for count in modes.iter() {
+        let (allocations, _) = count_allocations(|| {
+            std::hint::black_box(count.count(text));
+            vec![]
+        });
+
+        assert_eq!(allocations, 0, "{:?}", count);
+    }
+}
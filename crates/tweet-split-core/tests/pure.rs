@@ -0,0 +1,54 @@
+// keeps the library free of IO, time, and randomness, apart from code
+// compiled out of the `pure` build, by searching its source for them
+
+use std::fs;
+use std::path::Path;
+
+const IMPURE: &[&str] = &[
+    "std::env",
+    "std::fs",
+    "std::io",
+    "std::net",
+    "std::process",
+    "std::thread",
+    "std::time",
+    "SystemTime",
+    "Instant",
+    "RandomState::new",
+    "print!(",
+    "println!(",
+    "dbg!(",
+];
+
+const NOT_PURE: &str = "#[cfg(not(feature = \"pure\"))]";
+
+#[test]
+fn it_only_reaches_outside_where_pure_builds_leave_it_out() {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut impure = vec![];
+
+    for entry in fs::read_dir(source).unwrap() {
+        let path = entry.unwrap().path();
+        let text = fs::read_to_string(&path).unwrap();
+        let lines = text.lines().collect::<Vec<&str>>();
+
+        for (number, line) in lines.iter().enumerate() {
+            let found = IMPURE.iter().any(|pattern| line.contains(pattern));
+            // the line is in a function whose signature follows the attribute
+            let excluded = lines[number.saturating_sub(2)..number]
+                .iter()
+                .any(|line| line.trim() == NOT_PURE);
+
+            if found && !excluded && !line.trim_start().starts_with("//") {
+                impure.push(format!(
+                    "{}:{}: {}",
+                    path.display(),
+                    number + 1,
+                    line.trim()
+                ));
+            }
+        }
+    }
+
+    assert!(impure.is_empty(), "{}", impure.join("\n"));
+}